@@ -1,17 +1,21 @@
-use std::collections::HashMap;
-
 use crate::bid::{Bid, Origins};
-use crate::fee::calculate_price_with_fees;
-use crate::market_core::AuctionArgs;
+use crate::fee::{calculate_price_with_fees, checked_payout, MAX_PAYOUT_LEN};
+use crate::market_core::{ArgsKind, AuctionArgs};
 use crate::sale::{
-    ext_contract, ext_self, Payout, GAS_FOR_FT_TRANSFER, GAS_FOR_NFT_TRANSFER, GAS_FOR_ROYALTIES,
-    NO_DEPOSIT,
+    ext_contract, ext_self, DELIMETER, GAS_FOR_FT_TRANSFER, GAS_FOR_NFT_TRANSFER, NO_DEPOSIT,
 };
 use crate::*;
-use near_sdk::{near_bindgen, promise_result_as_success};
+use near_contract_standards::non_fungible_token::hash_account_id;
+use near_sdk::{near_bindgen, promise_result_as_success, Gas};
 // should check calculation
 pub const EXTENSION_DURATION: u64 = 15 * 60 * NANOS_PER_SEC; // 15 minutes
 pub const MAX_DURATION: u64 = 1000 * 60 * 60 * 24 * NANOS_PER_SEC; // 1000 days
+// Maximum length of the serialized relist args, they take a storage slot of a sale
+pub const MAX_RELIST_LENGTH: usize = 500;
+// Unused relist args can be removed by anyone after this time
+pub const RELIST_TIMEOUT: u64 = 30 * 60 * 60 * 24 * NANOS_PER_SEC; // 30 days
+// Gas for `resolve_finish_auction` itself, FT payouts are attached `GAS_FOR_FT_TRANSFER` each on top
+pub const GAS_FOR_RESOLVE_FINISH_AUCTION: Gas = Gas(25_000_000_000_000);
 
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
@@ -31,6 +35,9 @@ pub struct Auction {
     pub end: u64,
 
     pub origins: Origins,
+    pub allowed_buyers: Option<Vec<AccountId>>,
+    // `ArgsKind` of the current bidder to list the token again after the auction is finished
+    pub relist: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -48,6 +55,78 @@ pub struct AuctionJson {
 
     pub start: U64,
     pub end: U64,
+    pub allowed_buyers: Option<Vec<AccountId>>,
+}
+
+// Reward for calling `finish_auction`, paid out of the protocol fee
+// `Fixed` amount is in the ft token of the auction
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub enum KeeperBounty {
+    Bps(u32),
+    Fixed(U128),
+}
+
+impl KeeperBounty {
+    // `Bps` are taken of the sale price without the buyer fees, the base of the protocol fee.
+    // The bounty can't exceed the protocol fee
+    pub fn amount(&self, sale_price: u128, protocol_fee: u128) -> u128 {
+        let bounty = match self {
+            KeeperBounty::Bps(bps) => sale_price * *bps as u128 / PAYOUT_TOTAL_VALUE,
+            KeeperBounty::Fixed(amount) => amount.0,
+        };
+        std::cmp::min(bounty, protocol_fee)
+    }
+}
+
+// Listing arguments of the auction winner
+// Used in `nft_on_approve` when the winner approves the token with an empty msg
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Relist {
+    pub owner_id: AccountId,
+    pub msg: String,
+    pub created_at: U64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+#[serde(crate = "near_sdk::serde")]
+pub enum AuctionStatus {
+    // The auction hasn't started yet
+    Upcoming,
+    // Bids can be added
+    Live,
+    // The auction has ended, but it's not finished or removed yet
+    Ended,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AuctionIdJson {
+    pub auction_id: U128,
+    pub auction_json: AuctionJson,
+}
+
+impl Auction {
+    pub fn status(&self) -> AuctionStatus {
+        let now = env::block_timestamp();
+        if self.end < now {
+            AuctionStatus::Ended
+        } else if self.start < now {
+            AuctionStatus::Live
+        } else {
+            AuctionStatus::Upcoming
+        }
+    }
+
+    pub fn is_allowed_buyer(&self, buyer_id: &AccountId) -> bool {
+        self.allowed_buyers
+            .as_ref()
+            .map(|buyers| buyers.contains(buyer_id))
+            .unwrap_or(true)
+    }
 }
 
 #[near_bindgen]
@@ -78,6 +157,10 @@ impl Market {
         let end = start + args.duration.0;
         let auction_id = self.market.next_auction_id;
         let origins = args.origins.unwrap_or_default();
+        let allowed_buyers = args.allowed_buyers.map(dedup_accounts);
+        if let Some(allowed_buyers) = allowed_buyers.as_ref() {
+            require!(!allowed_buyers.is_empty(), "allowed_buyers should not be empty");
+        }
         let auction = Auction {
             owner_id,
             approval_id,
@@ -92,9 +175,55 @@ impl Market {
             start,
             end,
             origins,
+            allowed_buyers,
+            relist: None,
         };
+        let mut by_owner_id = self
+            .market
+            .auctions_by_owner_id
+            .get(&auction.owner_id)
+            .unwrap_or_else(|| {
+                UnorderedSet::new(
+                    StorageKey::AuctionsByOwnerIdInner {
+                        account_id_hash: hash_account_id(&auction.owner_id),
+                    }
+                    .try_to_vec()
+                    .unwrap(),
+                )
+            });
+        by_owner_id.insert(&auction_id);
+        self.market
+            .auctions_by_owner_id
+            .insert(&auction.owner_id, &by_owner_id);
         self.market.auctions.insert(&auction_id, &auction);
+        self.market.auctions_by_end.insert(&(auction.end, auction_id), &());
+        self.market.auction_ids.insert(&auction_id, &());
+        if let Some(allowed_buyers) = auction.allowed_buyers.as_ref() {
+            for buyer_id in allowed_buyers {
+                let mut by_allowed_buyer = self
+                    .market
+                    .auctions_by_allowed_buyer
+                    .get(buyer_id)
+                    .unwrap_or_else(|| {
+                        UnorderedSet::new(
+                            StorageKey::AuctionsByAllowedBuyerInner {
+                                account_id_hash: hash_account_id(buyer_id),
+                            }
+                            .try_to_vec()
+                            .unwrap(),
+                        )
+                    });
+                by_allowed_buyer.insert(&auction_id);
+                self.market
+                    .auctions_by_allowed_buyer
+                    .insert(buyer_id, &by_allowed_buyer);
+                self.market
+                    .auction_ids_by_allowed_buyer
+                    .insert(&(buyer_id.clone(), auction_id), &());
+            }
+        }
         self.market.next_auction_id += 1;
+        self.internal_update_auction_stats(&auction.nft_contract_id, true);
 
         let auction_json = self.json_from_auction(auction);
 
@@ -110,6 +239,7 @@ impl Market {
         auction_id: U128,
         token_type: TokenType,
         origins: Option<Origins>,
+        relist: Option<ArgsKind>,
     ) {
         let ft_token_id = self.token_type_to_ft_token_type(token_type);
         require!(
@@ -129,7 +259,19 @@ impl Market {
             auction.owner_id != env::predecessor_account_id(),
             "Cannot bid on your own auction"
         );
-            let deposit = env::attached_deposit();
+        self.assert_not_blocked(&env::predecessor_account_id());
+        require!(
+            auction.is_allowed_buyer(&env::predecessor_account_id()),
+            "The auction is reserved for other buyers"
+        );
+        require!(
+            Fees::new(origins.clone().unwrap_or_default(), &auction.origins)
+                .max_len_payout(&auction.owner_id, 0)
+                .is_some(),
+            "Too many fees and bid refunds for the payout"
+        );
+        let previous_end = auction.end;
+        let deposit = env::attached_deposit();
         let min_deposit =
             calculate_price_with_fees(self.get_minimal_next_bid(auction_id), origins.as_ref());
 
@@ -140,6 +282,10 @@ impl Market {
         );
         //Return previous bid
         if let Some(previous_bid) = auction.bid {
+            if auction.relist.is_some() {
+                self.internal_remove_relist_owner(&previous_bid.owner_id);
+            }
+            self.internal_remove_auction_bidder(&previous_bid.owner_id, auction_id.into());
             self.refund_bid(ft_token_id, previous_bid.owner_id, previous_bid.price);
         }
         // If the price is bigger than the buy_out_price, the auction end is set to the current time
@@ -160,13 +306,92 @@ impl Market {
         };
         // Extend the auction if the bid is added EXTENSION_DURATION (15 min) before the auction end
         // and the token is not bought out
+        self.internal_add_auction_bidder(&bid.owner_id, auction_id.into());
+        auction.relist = relist.map(relist_msg);
+        if auction.relist.is_some() {
+            self.internal_add_relist_owner(&bid.owner_id);
+        }
         auction.bid = Some(bid);
         if auction.end - env::block_timestamp() < EXTENSION_DURATION && !bought_out {
             auction.end = env::block_timestamp() + EXTENSION_DURATION;
         }
+        if auction.end != previous_end {
+            self.market
+                .auctions_by_end
+                .remove(&(previous_end, auction_id.into()));
+            self.market
+                .auctions_by_end
+                .insert(&(auction.end, auction_id.into()), &());
+        }
+        self.market.auctions.insert(&auction_id.into(), &auction);
+    }
+
+    // Sets or removes the listing arguments which will be used after the auction is won
+    // Can be called by the current bidder, the args take a slot of its storage deposit
+    pub fn set_auction_relist(&mut self, auction_id: U128, relist: Option<ArgsKind>) {
+        let mut auction = self
+            .market
+            .auctions
+            .get(&auction_id.into())
+            .unwrap_or_else(|| env::panic_str("Auction is not active"));
+        let bidder_id = env::predecessor_account_id();
+        require!(
+            auction.bid.as_ref().map(|bid| &bid.owner_id) == Some(&bidder_id),
+            "Only the current bidder can set relist args"
+        );
+        let relist = relist.map(relist_msg);
+        match (auction.relist.is_some(), relist.is_some()) {
+            (false, true) => self.internal_add_relist_owner(&bidder_id),
+            (true, false) => self.internal_remove_relist_owner(&bidder_id),
+            _ => {}
+        }
+        auction.relist = relist;
         self.market.auctions.insert(&auction_id.into(), &auction);
     }
 
+    // Sets or removes the listing arguments of the already finished auction
+    // Can be called by the auction winner
+    pub fn set_relist(
+        &mut self,
+        nft_contract_id: AccountId,
+        token_id: TokenId,
+        relist: Option<ArgsKind>,
+    ) {
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, DELIMETER, token_id);
+        let mut pending = self
+            .market
+            .relists
+            .get(&contract_and_token_id)
+            .unwrap_or_else(|| env::panic_str("No relist args for the token"));
+        require!(
+            pending.owner_id == env::predecessor_account_id(),
+            "Only the auction winner can set relist args"
+        );
+        if let Some(args) = relist {
+            pending.msg = relist_msg(args);
+            self.market.relists.insert(&contract_and_token_id, &pending);
+        } else {
+            self.market.relists.remove(&contract_and_token_id);
+            self.internal_remove_relist_owner(&pending.owner_id);
+        }
+    }
+
+    // Removes the relist args which weren't used for `RELIST_TIMEOUT` after the auction
+    // Can be called by anyone, releases the storage of the auction winner
+    pub fn remove_expired_relist(&mut self, nft_contract_id: AccountId, token_id: TokenId) {
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, DELIMETER, token_id);
+        let relist = self
+            .market
+            .relists
+            .remove(&contract_and_token_id)
+            .unwrap_or_else(|| env::panic_str("No relist args for the token"));
+        require!(
+            env::block_timestamp() >= relist.created_at.0 + RELIST_TIMEOUT,
+            "Relist args can be removed only after the timeout"
+        );
+        self.internal_remove_relist_owner(&relist.owner_id);
+    }
+
     // Cancels the auction if it doesn't have a bid yet
     // Can be called by the auction owner
     #[payable]
@@ -185,94 +410,142 @@ impl Market {
             auction.bid.is_none(),
             "Can't cancel the auction after the first bid is made"
         );
-        self.market.auctions.remove(&auction_id.into());
+        self.internal_remove_auction(auction_id.into());
+    }
+
+    // Removes the auction which has ended without bids
+    // Can be called by anyone, releases the storage of the auction owner
+    pub fn remove_expired_auction(&mut self, auction_id: U128) {
+        let auction = self.internal_remove_auction(auction_id.into());
+        require!(
+            env::block_timestamp() > auction.end,
+            "Auction can be removed only after the end time"
+        );
+        require!(
+            auction.bid.is_none(),
+            "Auction with a bid should be finished with finish_auction"
+        );
     }
 
     // Finishes the auction if it has reached its end
     // Can be called by anyone
     pub fn finish_auction(&mut self, auction_id: U128) -> Promise {
-        let auction = self
-            .market
-            .auctions
-            .remove(&auction_id.into())
-            .unwrap_or_else(|| env::panic_str("Auction is not active"));
+        let auction = self.internal_remove_auction(auction_id.into());
         require!(
             env::block_timestamp() > auction.end,
             "Auction can be finalized only after the end time"
         );
-        let final_bid = auction
-            .bid
-            .unwrap_or_else(|| env::panic_str("Can finalize only if there is a bid"));
-        let mut buyer = final_bid.origins;
-        buyer.insert(env::current_account_id(), PROTOCOL_FEE as u32);
-        let mut seller_fee = HashMap::with_capacity(auction.origins.len() + 1);
-        seller_fee.extend(auction.origins.clone()); // TODO: dodge this clone
-        seller_fee.insert(env::current_account_id(), PROTOCOL_FEE as u32);
-        let fees = fee::Fees {
-            buyer,
-            seller: seller_fee,
-        };
+        require!(auction.bid.is_some(), "Can finalize only if there is a bid");
+        self.internal_finish_auction(auction)
+    }
+
+    // Transfers the token to the final bid owner
+    // The auction should be already removed, ended and have a bid
+    pub(crate) fn internal_finish_auction(&mut self, auction: Auction) -> Promise {
+        let final_bid = auction.bid.expect("Can finalize only if there is a bid");
+        let fees = Fees::new(final_bid.origins, &auction.origins);
+        // The NFT contract rejects the transfer if its payout doesn't fit with the fees,
+        // the number of fees is checked when the bid is added
+        let max_len_payout = fees.max_len_payout(&auction.owner_id, 0).unwrap_or(1);
+        // NFT contract pays royalties out of the price without the buyer fees
         ext_contract::nft_transfer_payout(
             final_bid.owner_id.clone(),
             auction.token_id.clone(),
             auction.approval_id,
-            Some(near_sdk::serde_json::to_string(&fees).expect("Failed to sereailize")),
-            final_bid.price,
-            10,
+            None,
+            fees.sale_price(final_bid.price.0).into(),
+            max_len_payout,
             auction.nft_contract_id.clone(),
             1,
             GAS_FOR_NFT_TRANSFER,
         )
         .then(ext_self::resolve_finish_auction(
-            auction.ft_token_id,
+            auction.ft_token_id.clone(),
             final_bid.owner_id.clone(),
             final_bid.price,
+            auction.owner_id,
+            fees,
+            env::predecessor_account_id(),
+            format!("{}{}{}", auction.nft_contract_id, DELIMETER, auction.token_id),
+            auction.relist,
             env::current_account_id(),
             NO_DEPOSIT,
-            GAS_FOR_ROYALTIES,
+            gas_for_resolve_finish_auction(&auction.ft_token_id),
         ))
     }
 
     // self callback
     // If transfer of token succeded - count fees and transfer payouts
     // If failed - refund price to buyer
+    #[allow(clippy::too_many_arguments)]
     #[private]
     pub fn resolve_finish_auction(
         &mut self,
         ft_token_id: AccountId,
         buyer_id: AccountId,
         price: U128,
+        owner_id: AccountId,
+        fees: Fees,
+        keeper_id: AccountId,
+        contract_and_token_id: ContractAndTokenId,
+        relist: Option<String>,
     ) -> U128 {
-        let payout_option = promise_result_as_success().and_then(|value| {
-            near_sdk::serde_json::from_slice::<Payout>(&value)
-                .ok()
-                .and_then(|payout| {
-                    if payout.payout.len() > 10 || payout.payout.is_empty() {
-                        env::log_str("Cannot have more than 10 payouts and sale.bids refunds");
-                        None
-                    } else {
-                        let mut remainder = price.0;
-                        for &value in payout.payout.values() {
-                            remainder = remainder.checked_sub(value.0)?;
-                        }
-                        if remainder <= 1 {
-                            Some(payout)
-                        } else {
-                            None
-                        }
-                    }
-                })
-        });
-        // is payout option valid?
-        let payout = if let Some(payout_option) = payout_option {
-            payout_option
+        let sale_price = fees.sale_price(price.0);
+        let value = if let Some(value) = promise_result_as_success() {
+            value
         } else {
+            // the token wasn't transferred, refund the buyer
+            if relist.is_some() {
+                self.internal_remove_relist_owner(&buyer_id);
+            }
             if ft_token_id == "near".parse().unwrap() {
                 Promise::new(buyer_id).transfer(u128::from(price));
             }
             // leave function and return all FTs in ft_resolve_transfer
             return price;
         };
+        // The token is transferred, so the buyer is never refunded from here on
+        let max_len_payout = fees.max_len_payout(&owner_id, 0).unwrap_or_default();
+        let mut payout = checked_payout(&value, &owner_id, sale_price, max_len_payout);
+        fees.apply(&mut payout, &owner_id, sale_price);
+
+        // Auctions don't have a token type, so they are recorded per token and NFT contract only
+        if let Some((nft_contract_id, token_id)) = contract_and_token_id.split_once(DELIMETER) {
+            self.internal_record_price(
+                &nft_contract_id.parse().unwrap(),
+                &token_id.to_string(),
+                None,
+                &ft_token_id,
+                sale_price.into(),
+            );
+        }
+
+        // The winner can list the token again by approving it with an empty msg
+        if let Some(msg) = relist {
+            let previous = self.market.relists.insert(
+                &contract_and_token_id,
+                &Relist {
+                    owner_id: buyer_id,
+                    msg,
+                    created_at: env::block_timestamp().into(),
+                },
+            );
+            if let Some(previous) = previous {
+                self.internal_remove_relist_owner(&previous.owner_id);
+            }
+        }
+
+        // Part of the protocol fee goes to the account which finished the auction
+        if let Some(keeper_bounty) = self.market.keeper_bounty.as_ref() {
+            let market_id = env::current_account_id();
+            let protocol_fee = payout.payout.get(&market_id).map(|p| p.0).unwrap_or(0);
+            let bounty = keeper_bounty.amount(sale_price, protocol_fee);
+            if bounty > 0 && keeper_id != market_id {
+                payout.payout.insert(market_id, U128(protocol_fee - bounty));
+                let keeper_payout = payout.payout.entry(keeper_id).or_insert(U128(0));
+                keeper_payout.0 += bounty;
+            }
+        }
 
         // NEAR payouts
         if ft_token_id == "near".parse().unwrap() {
@@ -298,6 +571,21 @@ impl Market {
         }
     }
 
+    // Sets the reward for calling `finish_auction`, `None` disables it
+    pub fn set_keeper_bounty(&mut self, keeper_bounty: Option<KeeperBounty>) {
+        require!(
+            env::predecessor_account_id() == self.market.owner_id,
+            "only owner can set the keeper bounty"
+        );
+        if let Some(KeeperBounty::Bps(bps)) = keeper_bounty {
+            require!(
+                bps as u128 <= PROTOCOL_FEE,
+                "Keeper bounty can't exceed the protocol fee"
+            );
+        }
+        self.market.keeper_bounty = keeper_bounty;
+    }
+
     fn token_type_to_ft_token_type(&self, token_type: TokenType) -> AccountId {
         let token_type = if let Some(token_type) = token_type {
             AccountId::new_unchecked(token_type)
@@ -324,6 +612,48 @@ impl Market {
             buy_out_price: auction.buy_out_price.map(|p| p.into()),
             start: auction.start.into(),
             end: auction.end.into(),
+            allowed_buyers: auction.allowed_buyers,
+        }
+    }
+}
+
+impl Market {
+    // Relist args take a slot of the owner's storage deposit, like a sale
+    pub(crate) fn internal_add_relist_owner(&mut self, owner_id: &AccountId) {
+        let relists = self.market.relists_by_owner_id.get(owner_id).unwrap_or_default();
+        self.market.relists_by_owner_id.insert(owner_id, &(relists + 1));
+        let paid_storage = self.market.storage_deposits.get(owner_id).unwrap_or(0);
+        let required_storage =
+            u128::from(self.internal_supply_by_owner_id(owner_id)) * STORAGE_PER_SALE;
+        require!(
+            paid_storage >= required_storage,
+            "Insufficient storage paid for relist args"
+        );
+    }
+
+    pub(crate) fn internal_remove_relist_owner(&mut self, owner_id: &AccountId) {
+        let relists = self.market.relists_by_owner_id.get(owner_id).unwrap_or_default();
+        if relists > 1 {
+            self.market.relists_by_owner_id.insert(owner_id, &(relists - 1));
+        } else {
+            self.market.relists_by_owner_id.remove(owner_id);
         }
     }
 }
+
+// Gas attached to the callback of `finish_auction`, including its payouts.
+// NEAR payouts are plain transfers, while each FT payout is a function call.
+// The keeper bounty can add one more payout to the ones limited by `MAX_PAYOUT_LEN`
+pub(crate) fn gas_for_resolve_finish_auction(ft_token_id: &AccountId) -> Gas {
+    if ft_token_id.as_str() == "near" {
+        GAS_FOR_RESOLVE_FINISH_AUCTION
+    } else {
+        Gas(GAS_FOR_RESOLVE_FINISH_AUCTION.0 + GAS_FOR_FT_TRANSFER.0 * (MAX_PAYOUT_LEN as u64 + 1))
+    }
+}
+
+fn relist_msg(args: ArgsKind) -> String {
+    let msg = near_sdk::serde_json::to_string(&args).unwrap();
+    require!(msg.len() <= MAX_RELIST_LENGTH, "Relist args are too long");
+    msg
+}
@@ -1,6 +1,8 @@
-use crate::auction::AuctionJson;
+use crate::auction::{AuctionIdJson, AuctionJson, AuctionStatus, KeeperBounty, Relist};
+use crate::sale::DELIMETER;
 use crate::common::*;
 use crate::*;
+use std::ops::Bound;
 
 #[near_bindgen]
 impl Market {
@@ -81,5 +83,239 @@ impl Market {
             .collect()
     }
 
+    // Returns private auctions reserved for the given buyer
+    pub fn get_auctions_by_allowed_buyer(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<AuctionJson> {
+        let by_allowed_buyer = self.market.auctions_by_allowed_buyer.get(&account_id);
+        let by_allowed_buyer = if let Some(by_allowed_buyer) = by_allowed_buyer {
+            by_allowed_buyer
+        } else {
+            return vec![];
+        };
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        by_allowed_buyer
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|auction_id| {
+                self.json_from_auction(self.market.auctions.get(&auction_id).unwrap())
+            })
+            .collect()
+    }
+
+    // Cursor is the id of the last auction of the previous page
+    pub fn get_auctions_page(
+        &self,
+        cursor: Option<U128>,
+        limit: Option<u64>,
+    ) -> Page<AuctionIdJson> {
+        let (auction_ids, next_cursor) = match cursor {
+            Some(cursor) => paginate(self.market.auction_ids.iter_from(cursor.0), limit),
+            None => paginate(self.market.auction_ids.iter(), limit),
+        };
+        self.auctions_page(
+            auction_ids.into_iter().map(|(auction_id, _)| auction_id),
+            next_cursor.map(|(auction_id, _)| auction_id.to_string()),
+        )
+    }
+
+    // Cursor is the id of the last auction of the previous page
+    pub fn get_auctions_by_allowed_buyer_page(
+        &self,
+        account_id: AccountId,
+        cursor: Option<U128>,
+        limit: Option<u64>,
+    ) -> Page<AuctionIdJson> {
+        let from = match cursor {
+            Some(cursor) => Bound::Excluded((account_id.clone(), cursor.0)),
+            None => Bound::Included((account_id.clone(), 0)),
+        };
+        let (keys, next_cursor) = paginate(
+            self.market
+                .auction_ids_by_allowed_buyer
+                .range((from, Bound::Unbounded))
+                .map(|(key, _)| key)
+                .take_while(|(buyer_id, _)| buyer_id == &account_id),
+            limit,
+        );
+        self.auctions_page(
+            keys.into_iter().map(|(_, auction_id)| auction_id),
+            next_cursor.map(|(_, auction_id)| auction_id.to_string()),
+        )
+    }
+
+    pub fn get_supply_auctions_by_owner_id(&self, account_id: AccountId) -> U64 {
+        let by_owner_id = self.market.auctions_by_owner_id.get(&account_id);
+        if let Some(by_owner_id) = by_owner_id {
+            U64(by_owner_id.len())
+        } else {
+            U64(0)
+        }
+    }
+
+    pub fn get_relist(&self, nft_contract_id: AccountId, token_id: TokenId) -> Option<Relist> {
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, DELIMETER, token_id);
+        self.market.relists.get(&contract_and_token_id)
+    }
+
+    pub fn get_keeper_bounty(&self) -> Option<KeeperBounty> {
+        self.market.keeper_bounty.clone()
+    }
+
+    // Returns auctions which have ended with a bid and wait for `finish_auction`
+    pub fn get_unsettled_auctions(
+        &self,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<AuctionIdJson> {
+        let now = env::block_timestamp();
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        self.market
+            .auctions
+            .iter()
+            .filter(|(_, auction)| auction.end < now && auction.bid.is_some())
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|(auction_id, auction)| AuctionIdJson {
+                auction_id: auction_id.into(),
+                auction_json: self.json_from_auction(auction),
+            })
+            .collect()
+    }
+
+    // Returns auctions with the given status sorted by the end time
+    pub fn get_auctions_by_status(
+        &self,
+        status: AuctionStatus,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<AuctionIdJson> {
+        let now = env::block_timestamp();
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        let auction_ids: Vec<u128> = if status == AuctionStatus::Ended {
+            self.market
+                .auctions_by_end
+                .iter()
+                .take_while(|((end, _), _)| *end < now)
+                .skip(start_index as usize)
+                .take(limit)
+                .map(|((_, auction_id), _)| auction_id)
+                .collect()
+        } else {
+            // Upcoming and live auctions end not earlier than now
+            self.market
+                .auctions_by_end
+                .iter_from((now.saturating_sub(1), u128::MAX))
+                .map(|((_, auction_id), _)| auction_id)
+                .filter(|auction_id| {
+                    self.market.auctions.get(auction_id).unwrap().status() == status
+                })
+                .skip(start_index as usize)
+                .take(limit)
+                .collect()
+        };
+        auction_ids
+            .into_iter()
+            .map(|auction_id| AuctionIdJson {
+                auction_id: auction_id.into(),
+                auction_json: self.json_from_auction(self.market.auctions.get(&auction_id).unwrap()),
+            })
+            .collect()
+    }
+
+    // Cursor is the `end||auction_id` of the last auction of the previous page
+    pub fn get_auctions_by_status_page(
+        &self,
+        status: AuctionStatus,
+        cursor: Option<String>,
+        limit: Option<u64>,
+    ) -> Page<AuctionIdJson> {
+        let now = env::block_timestamp();
+        let cursor = cursor.map(|cursor| {
+            let (end, auction_id) = cursor
+                .split_once(DELIMETER)
+                .unwrap_or_else(|| env::panic_str("Invalid cursor"));
+            let end: u64 = end.parse().unwrap_or_else(|_| env::panic_str("Invalid cursor"));
+            let auction_id: u128 = auction_id
+                .parse()
+                .unwrap_or_else(|_| env::panic_str("Invalid cursor"));
+            (end, auction_id)
+        });
+        let (keys, next_cursor) = if status == AuctionStatus::Ended {
+            let from = match cursor {
+                Some(cursor) => Bound::Excluded(cursor),
+                None => Bound::Unbounded,
+            };
+            paginate(
+                self.market
+                    .auctions_by_end
+                    .range((from, Bound::Unbounded))
+                    .map(|(key, _)| key)
+                    .take_while(|(end, _)| *end < now),
+                limit,
+            )
+        } else {
+            // Upcoming and live auctions end not earlier than now
+            let live_from = (now.saturating_sub(1), u128::MAX);
+            let from = match cursor {
+                Some(cursor) if cursor > live_from => cursor,
+                _ => live_from,
+            };
+            paginate(
+                self.market
+                    .auctions_by_end
+                    .iter_from(from)
+                    .map(|(key, _)| key)
+                    .filter(|(_, auction_id)| {
+                        self.market.auctions.get(auction_id).unwrap().status() == status
+                    }),
+                limit,
+            )
+        };
+        self.auctions_page(
+            keys.into_iter().map(|(_, auction_id)| auction_id),
+            next_cursor.map(|(end, auction_id)| format!("{}{}{}", end, DELIMETER, auction_id)),
+        )
+    }
+
+    // Returns the status of each auction, `None` if the auction doesn't exist
+    pub fn get_auction_statuses(&self, auction_ids: Vec<U128>) -> Vec<Option<AuctionStatus>> {
+        auction_ids
+            .into_iter()
+            .map(|auction_id| {
+                self.market
+                    .auctions
+                    .get(&auction_id.into())
+                    .map(|auction| auction.status())
+            })
+            .collect()
+    }
+
     //pub fn get_bid_total_amount() -> U128;
 }
+
+impl Market {
+    pub(crate) fn auctions_page(
+        &self,
+        auction_ids: impl Iterator<Item = u128>,
+        next_cursor: Option<String>,
+    ) -> Page<AuctionIdJson> {
+        Page {
+            items: auction_ids
+                .map(|auction_id| AuctionIdJson {
+                    auction_id: auction_id.into(),
+                    auction_json: self
+                        .json_from_auction(self.market.auctions.get(&auction_id).unwrap()),
+                })
+                .collect(),
+            next_cursor,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use near_sdk::Gas;
+
+use crate::auction::gas_for_resolve_finish_auction;
+use crate::sale::GAS_FOR_NFT_TRANSFER;
+use crate::*;
+
+// Gas to process one sale in cleanup methods, including refunds of its bids
+pub const GAS_FOR_SALE_CLEANUP: Gas = Gas(40_000_000_000_000);
+// Gas to remove one auction and schedule its promises in `finish_auctions`.
+// The gas attached to the NFT transfer and the callback is added for each finished auction
+pub const GAS_FOR_FINISH_AUCTION: Gas = Gas(5_000_000_000_000);
+
+fn has_gas_for(gas: Gas) -> bool {
+    env::prepaid_gas().0 - env::used_gas().0 > gas.0
+}
+
+// Batch methods process up to `limit` items starting from `from_index`
+// and stop earlier if there is not enough gas left.
+// They return the index to continue from, or nothing if the end is reached.
+// Removed items are replaced by the last item of the list, so items which are moved
+// between calls can be skipped, they are processed by the next pass from the start.
+#[near_bindgen]
+impl Market {
+    // Finishes ended auctions which have a bid
+    pub fn finish_auctions(&mut self, from_index: Option<U64>, limit: u64) -> Option<U64> {
+        let now = env::block_timestamp();
+        let mut index = from_index.map(u64::from).unwrap_or_default();
+        let mut visited = 0;
+        while visited < limit && index < self.market.auctions.len() {
+            let auction_id = self.market.auctions.keys_as_vector().get(index).unwrap();
+            let auction = self.market.auctions.get(&auction_id).unwrap();
+            let is_finished = auction.end < now && auction.bid.is_some();
+            let gas = if is_finished {
+                Gas(GAS_FOR_FINISH_AUCTION.0
+                    + GAS_FOR_NFT_TRANSFER.0
+                    + gas_for_resolve_finish_auction(&auction.ft_token_id).0)
+            } else {
+                GAS_FOR_FINISH_AUCTION
+            };
+            if !has_gas_for(gas) {
+                break;
+            }
+            visited += 1;
+            if is_finished {
+                // the last auction is moved to the current index
+                self.internal_remove_auction(auction_id);
+                self.internal_finish_auction(auction);
+            } else {
+                index += 1;
+            }
+        }
+        if index < self.market.auctions.len() {
+            Some(U64(index))
+        } else {
+            None
+        }
+    }
+
+    // Removes ended auctions without bids
+    pub fn remove_expired_auctions(&mut self, from_index: Option<U64>, limit: u64) -> Option<U64> {
+        let now = env::block_timestamp();
+        let mut index = from_index.map(u64::from).unwrap_or_default();
+        let mut visited = 0;
+        while visited < limit && index < self.market.auctions.len() {
+            if !has_gas_for(GAS_FOR_SALE_CLEANUP) {
+                break;
+            }
+            visited += 1;
+            let auction_id = self.market.auctions.keys_as_vector().get(index).unwrap();
+            let auction = self.market.auctions.get(&auction_id).unwrap();
+            if auction.end < now && auction.bid.is_none() {
+                // the last auction is moved to the current index
+                self.internal_remove_auction(auction_id);
+            } else {
+                index += 1;
+            }
+        }
+        if index < self.market.auctions.len() {
+            Some(U64(index))
+        } else {
+            None
+        }
+    }
+
+    // Refunds and removes expired bids of the sales.
+    // The winning bid of an ended sale with settlement at the end is left for `finish_sale`
+    pub fn purge_expired_bids(&mut self, from_index: Option<U64>, limit: u64) -> Option<U64> {
+        let mut index = from_index.map(u64::from).unwrap_or_default();
+        let end = std::cmp::min(index.saturating_add(limit), self.market.sales.len());
+        while index < end {
+            if !has_gas_for(GAS_FOR_SALE_CLEANUP) {
+                break;
+            }
+            let contract_and_token_id = self.market.sales.keys_as_vector().get(index).unwrap();
+            let mut sale = self.market.sales.get(&contract_and_token_id).unwrap();
+            if self.refund_expired_bids(&mut sale) {
+                self.internal_insert_sale(&contract_and_token_id, &sale);
+            }
+            index += 1;
+        }
+        if index < self.market.sales.len() {
+            Some(U64(index))
+        } else {
+            None
+        }
+    }
+
+    // Removes finished sales which don't have bids
+    pub fn remove_expired_sales(&mut self, from_index: Option<U64>, limit: u64) -> Option<U64> {
+        let now = env::block_timestamp();
+        let mut index = from_index.map(u64::from).unwrap_or_default();
+        let mut visited = 0;
+        while visited < limit && index < self.market.sales.len() {
+            if !has_gas_for(GAS_FOR_SALE_CLEANUP) {
+                break;
+            }
+            visited += 1;
+            let contract_and_token_id = self.market.sales.keys_as_vector().get(index).unwrap();
+            let sale = self.market.sales.get(&contract_and_token_id).unwrap();
+            let is_finished = sale.end.map(|end| now >= end).unwrap_or(false);
+            if is_finished && sale.bids.is_empty() {
+                // the last sale is moved to the current index
+                self.internal_remove_sale(sale.nft_contract_id, sale.token_id);
+            } else {
+                index += 1;
+            }
+        }
+        if index < self.market.sales.len() {
+            Some(U64(index))
+        } else {
+            None
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use near_sdk::assert_one_yocto;
 
-use crate::fee::{calculate_actual_amount, calculate_origins};
+use crate::fee::{calculate_actual_amount, calculate_origins, Fees};
 use crate::sale::{
     ext_contract, ContractAndTokenId, FungibleTokenId, Sale, DELIMETER, GAS_FOR_FT_TRANSFER,
 };
@@ -22,6 +22,10 @@ pub struct Bid {
 }
 
 impl Bid {
+    pub fn is_same(&self, other: &Bid) -> bool {
+        self.owner_id == other.owner_id && self.price == other.price && self.start == other.start
+    }
+
     pub fn in_limits(&self) -> bool {
         let mut res_start = true;
         let mut res_end = true;
@@ -37,6 +41,27 @@ impl Bid {
 pub type Bids = HashMap<FungibleTokenId, Vec<Bid>>;
 pub type Origins = HashMap<AccountId, u32>;
 
+// Number of bids in all ft tokens, each of them is refunded separately
+pub(crate) fn bids_count(bids: &Bids) -> usize {
+    bids.values().map(Vec::len).sum()
+}
+
+// Any bid of the sale can be accepted and the sale can be bought without origins,
+// so that the fees together with the refunds of the rest of the bids fit into the payout
+fn assert_bids_fit_payout(sale: &Sale) {
+    let count = bids_count(&sale.bids);
+    let fits = |origins: &Origins, refunds: usize| {
+        Fees::new(origins.clone(), &sale.origins)
+            .max_len_payout(&sale.owner_id, refunds)
+            .is_some()
+    };
+    require!(
+        fits(&Origins::new(), count)
+            && sale.bids.values().flatten().all(|bid| fits(&bid.origins, count - 1)),
+        "Too many bids and origins for the payout"
+    );
+}
+
 #[near_bindgen]
 impl Market {
     // Adds a bid if it is higher than the last bid of this ft_token_id
@@ -99,8 +124,9 @@ impl Market {
             self.refund_bid(ft_token_id, early_bid.owner_id.clone(), early_bid.price);
             bids_for_token_id.remove(0);
         }
+        assert_bids_fit_payout(sale);
 
-        self.market.sales.insert(&contract_and_token_id, sale);
+        self.internal_insert_sale(&contract_and_token_id, sale);
     }
 
     #[payable]
@@ -113,7 +139,21 @@ impl Market {
     ) {
         assert_one_yocto();
         let owner_id = env::predecessor_account_id();
-        self.internal_remove_bid(nft_contract_id, &ft_token_id, token_id, &owner_id, price);
+        let contract_and_token_id = format!("{}{}{}", &nft_contract_id, DELIMETER, token_id);
+        let settlement_bid = self
+            .market
+            .sales
+            .get(&contract_and_token_id)
+            .and_then(|sale| sale.settlement_bid());
+        let bid = self
+            .internal_remove_bid(nft_contract_id, &ft_token_id, token_id, &owner_id, price)
+            .expect("No such bid");
+        if let Some((settlement_ft_token_id, settlement_bid)) = settlement_bid {
+            require!(
+                settlement_ft_token_id != ft_token_id || !settlement_bid.is_same(&bid),
+                "The winning bid can't be cancelled until the sale is finished"
+            );
+        }
         self.refund_bid(ft_token_id, owner_id, price);
     }
 
@@ -127,9 +167,21 @@ impl Market {
         owner_id: AccountId,
         price: U128,
     ) {
+        let contract_and_token_id = format!("{}{}{}", &nft_contract_id, DELIMETER, token_id);
+        let settlement_bid = self
+            .market
+            .sales
+            .get(&contract_and_token_id)
+            .and_then(|sale| sale.settlement_bid());
         let bid = self
             .internal_remove_bid(nft_contract_id, &ft_token_id, token_id, &owner_id, price)
             .expect("No such bid");
+        if let Some((settlement_ft_token_id, settlement_bid)) = settlement_bid {
+            require!(
+                settlement_ft_token_id != ft_token_id || !settlement_bid.is_same(&bid),
+                "The winning bid can't be cancelled until the sale is finished"
+            );
+        }
         if let Some(end) = bid.end {
             let is_finished = env::block_timestamp() >= end.0;
             require!(is_finished, "The bid hasn't ended yet");
@@ -158,7 +210,17 @@ impl Market {
             .sales
             .get(&contract_and_token_id)
             .expect("No sale");
+        // The winning bid of an ended settlement sale is kept for `finish_sale`
+        let settlement_bid = sale
+            .settlement_bid()
+            .filter(|(settlement_ft_token_id, _)| settlement_ft_token_id == &ft_token_id)
+            .map(|(_, bid)| bid);
         bid_vec.retain(|bid_from_vec| {
+            if let Some(bid) = settlement_bid.as_ref() {
+                if bid.is_same(bid_from_vec) {
+                    return true;
+                }
+            }
             let mut not_finished = true;
             if let Some(end) = bid_from_vec.end {
                 //is_finished &= env::block_timestamp() >= end.0;
@@ -180,11 +242,39 @@ impl Market {
             // If there are some bids left, add a vector of valid bids
             sale.bids.insert(ft_token_id.clone(), bid_vec.to_vec());
         };
-        self.market.sales.insert(&contract_and_token_id, &sale);
+        self.internal_insert_sale(&contract_and_token_id, &sale);
     }
 }
 
 impl Market {
+    // Refunds and removes all expired bids of the sale, except the one kept for `finish_sale`
+    // Returns true if any bid was removed
+    pub(crate) fn refund_expired_bids(&mut self, sale: &mut Sale) -> bool {
+        let now = env::block_timestamp();
+        let settlement_bid = sale.settlement_bid();
+        let mut expired = vec![];
+        for (ft_token_id, bids) in sale.bids.iter_mut() {
+            bids.retain(|bid| {
+                let is_settlement_bid = matches!(
+                    settlement_bid.as_ref(),
+                    Some((id, winning)) if id == ft_token_id && winning.is_same(bid)
+                );
+                let is_finished =
+                    !is_settlement_bid && bid.end.map(|end| now >= end.0).unwrap_or(false);
+                if is_finished {
+                    expired.push((ft_token_id.clone(), bid.owner_id.clone(), bid.price));
+                }
+                !is_finished
+            });
+        }
+        sale.bids.retain(|_, bids| !bids.is_empty());
+        let removed = !expired.is_empty();
+        for (ft_token_id, owner_id, price) in expired {
+            self.refund_bid(ft_token_id, owner_id, price);
+        }
+        removed
+    }
+
     pub(crate) fn refund_all_bids(&mut self, bids_map: &Bids) {
         for (ft, bids) in bids_map {
             for bid in bids {
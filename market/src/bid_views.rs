@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::ops::Bound;
+
+use crate::auction::AuctionIdJson;
+use crate::common::*;
+use crate::sale::{FungibleTokenId, SaleJson};
+use crate::*;
+
+#[near_bindgen]
+impl Market {
+    pub fn get_supply_sales_by_bidder(&self, account_id: AccountId) -> U64 {
+        let by_bidder = self.market.sales_by_bidder.get(&account_id);
+        if let Some(by_bidder) = by_bidder {
+            U64(by_bidder.len())
+        } else {
+            U64(0)
+        }
+    }
+
+    // Returns sales where the account has at least one bid
+    pub fn get_sales_by_bidder(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<SaleJson> {
+        let by_bidder = if let Some(by_bidder) = self.market.sales_by_bidder.get(&account_id) {
+            by_bidder
+        } else {
+            return vec![];
+        };
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        by_bidder
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|contract_and_token_id| {
+                self.json_from_sale(self.market.sales.get(&contract_and_token_id).unwrap())
+            })
+            .collect()
+    }
+
+    // Cursor is the `nft_contract_id||token_id` of the last sale of the previous page
+    pub fn get_sales_by_bidder_page(
+        &self,
+        account_id: AccountId,
+        cursor: Option<String>,
+        limit: Option<u64>,
+    ) -> Page<SaleJson> {
+        self.sales_page_by(&self.market.sale_ids_by_bidder, account_id, cursor, limit)
+    }
+
+    pub fn get_supply_auctions_by_bidder(&self, account_id: AccountId) -> U64 {
+        let by_bidder = self.market.auctions_by_bidder.get(&account_id);
+        if let Some(by_bidder) = by_bidder {
+            U64(by_bidder.len())
+        } else {
+            U64(0)
+        }
+    }
+
+    // Returns auctions where the account has the current bid
+    pub fn get_auctions_by_bidder(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<AuctionIdJson> {
+        let by_bidder = if let Some(by_bidder) = self.market.auctions_by_bidder.get(&account_id)
+        {
+            by_bidder
+        } else {
+            return vec![];
+        };
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        by_bidder
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|auction_id| {
+                let auction = self.market.auctions.get(&auction_id).unwrap();
+                AuctionIdJson {
+                    auction_id: auction_id.into(),
+                    auction_json: self.json_from_auction(auction),
+                }
+            })
+            .collect()
+    }
+
+    // Cursor is the id of the last auction of the previous page
+    pub fn get_auctions_by_bidder_page(
+        &self,
+        account_id: AccountId,
+        cursor: Option<U128>,
+        limit: Option<u64>,
+    ) -> Page<AuctionIdJson> {
+        let from = match cursor {
+            Some(cursor) => Bound::Excluded((account_id.clone(), cursor.0)),
+            None => Bound::Included((account_id.clone(), 0)),
+        };
+        let (keys, next_cursor) = paginate(
+            self.market
+                .auction_ids_by_bidder
+                .range((from, Bound::Unbounded))
+                .map(|(key, _)| key)
+                .take_while(|(bidder_id, _)| bidder_id == &account_id),
+            limit,
+        );
+        self.auctions_page(
+            keys.into_iter().map(|(_, auction_id)| auction_id),
+            next_cursor.map(|(_, auction_id)| auction_id.to_string()),
+        )
+    }
+
+    // Total amount of the account bids held by the market in each ft token
+    pub fn get_escrowed_by_bidder(&self, account_id: AccountId) -> HashMap<FungibleTokenId, U128> {
+        let mut escrowed: HashMap<FungibleTokenId, U128> = HashMap::new();
+        if let Some(by_bidder) = self.market.sales_by_bidder.get(&account_id) {
+            for contract_and_token_id in by_bidder.iter() {
+                let sale = self.market.sales.get(&contract_and_token_id).unwrap();
+                for (ft_token_id, bids) in sale.bids {
+                    for bid in bids.iter().filter(|bid| bid.owner_id == account_id) {
+                        escrowed.entry(ft_token_id.clone()).or_insert(U128(0)).0 += bid.price.0;
+                    }
+                }
+            }
+        }
+        if let Some(by_bidder) = self.market.auctions_by_bidder.get(&account_id) {
+            for auction_id in by_bidder.iter() {
+                let auction = self.market.auctions.get(&auction_id).unwrap();
+                if let Some(bid) = auction.bid {
+                    escrowed.entry(auction.ft_token_id).or_insert(U128(0)).0 += bid.price.0;
+                }
+            }
+        }
+        escrowed
+    }
+}
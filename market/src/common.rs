@@ -1,6 +1,6 @@
 pub use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    collections::{LazyOption, LookupSet, LookupMap, UnorderedMap, UnorderedSet},
+    collections::{LazyOption, LookupSet, LookupMap, TreeMap, UnorderedMap, UnorderedSet},
     env::{self, STORAGE_PRICE_PER_BYTE},
     json_types::{U128, U64},
     near_bindgen, require,
@@ -15,4 +15,40 @@ pub use near_contract_standards::non_fungible_token::{
     refund_deposit, NonFungibleToken, Token, TokenId,
 };
 
-pub const NANOS_PER_SEC: u64 = 1_000_000_000;
\ No newline at end of file
+pub const NANOS_PER_SEC: u64 = 1_000_000_000;
+
+// Page of a list view, `next_cursor` should be passed as `cursor` to get the next page
+// Unlike `from_index`, the cursor stays valid when items are removed from the list
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+// Takes up to `limit` keys, the last one is returned as the cursor if there are more keys
+pub(crate) fn paginate<K: Clone>(
+    keys: impl Iterator<Item = K>,
+    limit: Option<u64>,
+) -> (Vec<K>, Option<K>) {
+    // An empty page would end the iteration without a cursor
+    require!(limit != Some(0), "Cannot provide limit of 0.");
+    let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+    let mut keys: Vec<K> = keys.take(limit.saturating_add(1)).collect();
+    if keys.len() > limit {
+        keys.pop();
+        let cursor = keys.last().cloned();
+        (keys, cursor)
+    } else {
+        (keys, None)
+    }
+}
+
+// Removes repeated accounts, keeping the order of the first occurrences
+pub(crate) fn dedup_accounts(accounts: Vec<AccountId>) -> Vec<AccountId> {
+    let mut seen = std::collections::HashSet::with_capacity(accounts.len());
+    accounts
+        .into_iter()
+        .filter(|account_id| seen.insert(account_id.clone()))
+        .collect()
+}
@@ -1,17 +1,112 @@
 use crate::*;
+use crate::sale::Payout;
 use crate::{bid::Origins, common::*};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub const PAYOUT_TOTAL_VALUE: u128 = 10_000;
 pub const PROTOCOL_FEE: u128 = 300; // 10_000 is 100%, so 300 is 3%
+// gas to do 10 FT transfers (and definitely 10 NEAR transfers)
+pub const MAX_PAYOUT_LEN: u32 = 10;
 
-#[derive(Serialize)]
+// Protocol fee and origins of both sides of the purchase.
+// The buyer pays its fees on top of the sale price, the seller's fees are taken from its payout
+#[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct Fees {
     pub buyer: HashMap<AccountId, u32>,
     pub seller: HashMap<AccountId, u32>,
 }
 
+impl Fees {
+    pub fn new(buyer_origins: Origins, seller_origins: &Origins) -> Self {
+        let mut buyer = buyer_origins;
+        buyer.insert(env::current_account_id(), PROTOCOL_FEE as u32);
+        let mut seller = HashMap::with_capacity(seller_origins.len() + 1);
+        seller.extend(seller_origins.clone());
+        seller.insert(env::current_account_id(), PROTOCOL_FEE as u32);
+        Self { buyer, seller }
+    }
+
+    // Part of the `price` which goes to the royalties and the seller, without the buyer fees
+    pub fn sale_price(&self, price: u128) -> u128 {
+        let buyer_value = calculate_origins(&self.buyer) as u128;
+        price - price * buyer_value / (PAYOUT_TOTAL_VALUE + buyer_value)
+    }
+
+    // Number of accounts the NFT contract can pay royalties to, so that the payout
+    // together with the fees and `refunds` fits into `MAX_PAYOUT_LEN` transfers.
+    // `None` if the fees and refunds alone don't leave room for the seller
+    pub fn max_len_payout(&self, seller_id: &AccountId, refunds: usize) -> Option<u32> {
+        let mut receivers: HashSet<&AccountId> = self.buyer.keys().collect();
+        receivers.extend(self.seller.keys().filter(|account_id| *account_id != seller_id));
+        let reserved = (receivers.len() + refunds) as u32;
+        MAX_PAYOUT_LEN.checked_sub(reserved).filter(|len| *len > 0)
+    }
+
+    // Adds the fees to the NFT `payout` of the `sale_price`.
+    // The seller's fees are reduced proportionally if its payout doesn't cover them
+    pub fn apply(&self, payout: &mut Payout, seller_id: &AccountId, sale_price: u128) {
+        let seller_fees: Vec<(&AccountId, u128)> = self
+            .seller
+            .iter()
+            .filter(|(account_id, _)| *account_id != seller_id)
+            .map(|(account_id, value)| (account_id, *value as u128 * sale_price / PAYOUT_TOTAL_VALUE))
+            .collect();
+        let total_fee: u128 = seller_fees.iter().map(|(_, fee)| fee).sum();
+        let seller_payout = payout.payout.get(seller_id).map(|p| p.0).unwrap_or(0);
+        let mut paid_fee = 0;
+        for (account_id, fee) in seller_fees {
+            let fee = if total_fee > seller_payout {
+                fee * seller_payout / total_fee
+            } else {
+                fee
+            };
+            if fee > 0 {
+                payout.payout.entry(account_id.clone()).or_insert(U128(0)).0 += fee;
+                paid_fee += fee;
+            }
+        }
+        if let Some(seller_payout) = payout.payout.get_mut(seller_id) {
+            seller_payout.0 -= paid_fee;
+        }
+        for (account_id, value) in self.buyer.iter() {
+            let fee = *value as u128 * sale_price / PAYOUT_TOTAL_VALUE;
+            payout.payout.entry(account_id.clone()).or_insert(U128(0)).0 += fee;
+        }
+    }
+}
+
+// The NFT `payout` if it splits the `sale_price` between at most `max_len` accounts,
+// otherwise the whole sale price goes to the seller.
+// Each account of the payout can lose 1 yocto on rounding, the remainder goes to the seller
+pub fn checked_payout(value: &[u8], seller_id: &AccountId, sale_price: u128, max_len: u32) -> Payout {
+    near_sdk::serde_json::from_slice::<Payout>(value)
+        .ok()
+        .and_then(|mut payout| {
+            let total = payout
+                .payout
+                .values()
+                .try_fold(0u128, |total, value| total.checked_add(value.0))?;
+            let remainder = sale_price.checked_sub(total)?;
+            if payout.payout.is_empty()
+                || payout.payout.len() > max_len as usize
+                || remainder > payout.payout.len() as u128
+            {
+                return None;
+            }
+            if let Some(seller_payout) = payout.payout.get_mut(seller_id) {
+                seller_payout.0 += remainder;
+            }
+            Some(payout)
+        })
+        .unwrap_or_else(|| {
+            env::log_str("Invalid payout from the NFT contract, the sale price goes to the seller");
+            Payout {
+                payout: HashMap::from([(seller_id.clone(), U128(sale_price))]),
+            }
+        })
+}
+
 pub fn calculate_origins(origins: &Origins) -> u32 {
     let mut total: u32 = 0;
     for val in origins.values() {
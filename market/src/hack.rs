@@ -6,7 +6,7 @@ impl Market {
     pub fn hack_finish_sale(&mut self, nft_contract_token: ContractAndTokenId) {
         let mut sale = self.market.sales.get(&nft_contract_token).expect("no sale");
         sale.end = Some(env::block_timestamp());
-        self.market.sales.insert(&nft_contract_token, &sale);
+        self.internal_insert_sale(&nft_contract_token, &sale);
     }
 
     pub fn hack_finish_bid(&mut self, nft_contract_token: ContractAndTokenId) {
@@ -19,12 +19,14 @@ impl Market {
         if let Some(bid) = bid {
             bid.end = Some(U64(env::block_timestamp()))
         }
-        self.market.sales.insert(&nft_contract_token, &sale);
+        self.internal_insert_sale(&nft_contract_token, &sale);
     }
 
     pub fn hack_finish_auction(&mut self, auction_id: U128) {
         let mut auction = self.market.auctions.get(&auction_id.into()).expect("no auction");
+        self.market.auctions_by_end.remove(&(auction.end, auction_id.into()));
         auction.end = env::block_timestamp();
+        self.market.auctions_by_end.insert(&(auction.end, auction_id.into()), &());
         self.market.auctions.insert(&auction_id.into(), &auction);
     }
 }
@@ -1,7 +1,11 @@
-use crate::bid::Bid;
+use std::collections::HashSet;
+
+use crate::auction::Auction;
+use crate::bid::{Bid, Bids};
 use crate::common::*;
-use crate::sale::{Sale, DELIMETER};
-use crate::Market;
+use crate::sale::{ContractAndTokenId, Sale, SaleConditions, DELIMETER};
+use crate::{Market, StorageKey};
+use near_contract_standards::non_fungible_token::hash_account_id;
 
 impl Market {
     pub(crate) fn internal_remove_sale(
@@ -42,24 +46,314 @@ impl Market {
                 .insert(&nft_contract_id, &by_nft_contract_id);
         }
 
-        // here AccountId is used as "token type", idk why so (adsick)
-        if let Some(token_type) = sale.token_type.to_owned() {
-            let mut by_nft_token_type = self
+        if let Some(token_type) = sale.token_type.as_ref() {
+            self.internal_remove_sale_by_token_type(token_type, &contract_and_token_id);
+        }
+
+        if let Some(allowed_buyers) = sale.allowed_buyers.as_ref() {
+            for buyer_id in allowed_buyers {
+                let mut by_allowed_buyer = self
+                    .market
+                    .by_allowed_buyer
+                    .get(buyer_id)
+                    .expect("No sale by allowed_buyer");
+                by_allowed_buyer.remove(&contract_and_token_id);
+                if by_allowed_buyer.is_empty() {
+                    self.market.by_allowed_buyer.remove(buyer_id);
+                } else {
+                    self.market
+                        .by_allowed_buyer
+                        .insert(buyer_id, &by_allowed_buyer);
+                }
+            }
+        }
+
+        self.internal_update_sale_bidders(&contract_and_token_id, Some(&sale.bids), None);
+        self.market.sale_ids.remove(&contract_and_token_id);
+        self.market
+            .sale_ids_by_owner_id
+            .remove(&(sale.owner_id.clone(), contract_and_token_id.clone()));
+        self.internal_update_price_index(
+            &nft_contract_id,
+            &token_id,
+            Some(&sale.sale_conditions),
+            None,
+        );
+        self.internal_update_sale_stats(
+            &nft_contract_id,
+            &token_id,
+            Some((&sale.sale_conditions, sale.token_type.as_ref())),
+            None,
+        );
+        sale
+    }
+
+    // Number of sales, auctions and relist args the account pays storage for
+    pub(crate) fn internal_supply_by_owner_id(&self, account_id: &AccountId) -> u64 {
+        let sales = self
+            .market
+            .by_owner_id
+            .get(account_id)
+            .map(|s| s.len())
+            .unwrap_or_default();
+        let auctions = self
+            .market
+            .auctions_by_owner_id
+            .get(account_id)
+            .map(|a| a.len())
+            .unwrap_or_default();
+        let relists = self
+            .market
+            .relists_by_owner_id
+            .get(account_id)
+            .unwrap_or_default();
+        sales + auctions + relists
+    }
+
+    pub(crate) fn internal_remove_auction(&mut self, auction_id: u128) -> Auction {
+        let auction = self
+            .market
+            .auctions
+            .remove(&auction_id)
+            .unwrap_or_else(|| env::panic_str("Auction is not active"));
+        let mut by_owner_id = self
+            .market
+            .auctions_by_owner_id
+            .get(&auction.owner_id)
+            .expect("No auction by_owner_id");
+        by_owner_id.remove(&auction_id);
+        if by_owner_id.is_empty() {
+            self.market.auctions_by_owner_id.remove(&auction.owner_id);
+        } else {
+            self.market
+                .auctions_by_owner_id
+                .insert(&auction.owner_id, &by_owner_id);
+        }
+        self.market
+            .auctions_by_end
+            .remove(&(auction.end, auction_id));
+        self.market.auction_ids.remove(&auction_id);
+        if let Some(bid) = auction.bid.as_ref() {
+            self.internal_remove_auction_bidder(&bid.owner_id, auction_id);
+        }
+        if let Some(allowed_buyers) = auction.allowed_buyers.as_ref() {
+            for buyer_id in allowed_buyers {
+                let mut by_allowed_buyer = self
+                    .market
+                    .auctions_by_allowed_buyer
+                    .get(buyer_id)
+                    .expect("No auction by allowed_buyer");
+                by_allowed_buyer.remove(&auction_id);
+                if by_allowed_buyer.is_empty() {
+                    self.market.auctions_by_allowed_buyer.remove(buyer_id);
+                } else {
+                    self.market
+                        .auctions_by_allowed_buyer
+                        .insert(buyer_id, &by_allowed_buyer);
+                }
+                self.market
+                    .auction_ids_by_allowed_buyer
+                    .remove(&(buyer_id.clone(), auction_id));
+            }
+        }
+        self.internal_update_auction_stats(&auction.nft_contract_id, false);
+        auction
+    }
+
+    // Stores the sale and updates `sales_by_bidder` according to its bids
+    pub(crate) fn internal_insert_sale(
+        &mut self,
+        contract_and_token_id: &ContractAndTokenId,
+        sale: &Sale,
+    ) {
+        let old_sale = self.market.sales.insert(contract_and_token_id, sale);
+        self.internal_update_sale_bidders(
+            contract_and_token_id,
+            old_sale.as_ref().map(|old_sale| &old_sale.bids),
+            Some(&sale.bids),
+        );
+    }
+
+    pub(crate) fn internal_update_sale_bidders(
+        &mut self,
+        contract_and_token_id: &ContractAndTokenId,
+        old: Option<&Bids>,
+        new: Option<&Bids>,
+    ) {
+        let old_bidders = bidders(old);
+        let new_bidders = bidders(new);
+        for bidder_id in old_bidders.difference(&new_bidders) {
+            let mut by_bidder = self
                 .market
-                .by_nft_token_type
-                .get(&token_type)
-                .expect("No sale by nft_token_type");
-            by_nft_token_type.remove(&contract_and_token_id);
-            if by_nft_token_type.is_empty() {
-                self.market.by_nft_token_type.remove(&token_type);
+                .sales_by_bidder
+                .get(bidder_id)
+                .expect("No sale by bidder");
+            by_bidder.remove(contract_and_token_id);
+            if by_bidder.is_empty() {
+                self.market.sales_by_bidder.remove(bidder_id);
             } else {
-                self.market
-                    .by_nft_token_type
-                    .insert(&token_type, &by_nft_token_type);
+                self.market.sales_by_bidder.insert(bidder_id, &by_bidder);
             }
+            self.market
+                .sale_ids_by_bidder
+                .remove(&(bidder_id.clone(), contract_and_token_id.clone()));
+        }
+        for bidder_id in new_bidders.difference(&old_bidders) {
+            let mut by_bidder = self
+                .market
+                .sales_by_bidder
+                .get(bidder_id)
+                .unwrap_or_else(|| {
+                    UnorderedSet::new(
+                        StorageKey::SalesByBidderInner {
+                            account_id_hash: hash_account_id(bidder_id),
+                        }
+                        .try_to_vec()
+                        .unwrap(),
+                    )
+                });
+            by_bidder.insert(contract_and_token_id);
+            self.market.sales_by_bidder.insert(bidder_id, &by_bidder);
+            self.market
+                .sale_ids_by_bidder
+                .insert(&(bidder_id.clone(), contract_and_token_id.clone()), &());
         }
+    }
 
-        sale
+    pub(crate) fn internal_add_auction_bidder(&mut self, bidder_id: &AccountId, auction_id: u128) {
+        let mut by_bidder = self
+            .market
+            .auctions_by_bidder
+            .get(bidder_id)
+            .unwrap_or_else(|| {
+                UnorderedSet::new(
+                    StorageKey::AuctionsByBidderInner {
+                        account_id_hash: hash_account_id(bidder_id),
+                    }
+                    .try_to_vec()
+                    .unwrap(),
+                )
+            });
+        by_bidder.insert(&auction_id);
+        self.market.auctions_by_bidder.insert(bidder_id, &by_bidder);
+        self.market
+            .auction_ids_by_bidder
+            .insert(&(bidder_id.clone(), auction_id), &());
+    }
+
+    pub(crate) fn internal_remove_auction_bidder(
+        &mut self,
+        bidder_id: &AccountId,
+        auction_id: u128,
+    ) {
+        let mut by_bidder = self
+            .market
+            .auctions_by_bidder
+            .get(bidder_id)
+            .expect("No auction by bidder");
+        by_bidder.remove(&auction_id);
+        if by_bidder.is_empty() {
+            self.market.auctions_by_bidder.remove(bidder_id);
+        } else {
+            self.market.auctions_by_bidder.insert(bidder_id, &by_bidder);
+        }
+        self.market
+            .auction_ids_by_bidder
+            .remove(&(bidder_id.clone(), auction_id));
+    }
+
+    // Moves the sale prices in `sales_by_price` from `old` to `new` sale conditions
+    pub(crate) fn internal_update_price_index(
+        &mut self,
+        nft_contract_id: &AccountId,
+        token_id: &TokenId,
+        old: Option<&SaleConditions>,
+        new: Option<&SaleConditions>,
+    ) {
+        update_price_index(
+            &mut self.market.sales_by_price,
+            nft_contract_id.as_str(),
+            token_id,
+            old,
+            new,
+            |hash| StorageKey::SalesByPriceInner {
+                contract_and_ft_hash: hash,
+            },
+        );
+    }
+
+    // Moves the sale prices in `token_type_sales_by_price` from `old` to `new` sale conditions
+    pub(crate) fn internal_update_token_type_price_index(
+        &mut self,
+        token_type: &str,
+        contract_and_token_id: &ContractAndTokenId,
+        old: Option<&SaleConditions>,
+        new: Option<&SaleConditions>,
+    ) {
+        update_price_index(
+            &mut self.market.token_type_sales_by_price,
+            token_type,
+            contract_and_token_id,
+            old,
+            new,
+            |hash| StorageKey::TokenTypeSalesByPriceInner {
+                token_type_and_ft_hash: hash,
+            },
+        );
+    }
+
+    pub(crate) fn internal_add_sale_by_token_type(
+        &mut self,
+        token_type: &str,
+        contract_and_token_id: &ContractAndTokenId,
+    ) {
+        // here AccountId is used as "token type", idk why so (adsick)
+        let mut by_nft_token_type = self
+            .market
+            .by_nft_token_type
+            .get(&token_type.to_owned())
+            .unwrap_or_else(|| {
+                UnorderedSet::new(
+                    StorageKey::ByNFTTokenTypeInner {
+                        token_type_hash: hash_account_id(&AccountId::new_unchecked(
+                            token_type.to_owned(),
+                        )),
+                    }
+                    .try_to_vec()
+                    .unwrap(),
+                )
+            });
+        by_nft_token_type.insert(contract_and_token_id);
+        self.market
+            .by_nft_token_type
+            .insert(&token_type.to_owned(), &by_nft_token_type);
+        self.market
+            .sale_ids_by_token_type
+            .insert(&(token_type.to_owned(), contract_and_token_id.clone()), &());
+    }
+
+    pub(crate) fn internal_remove_sale_by_token_type(
+        &mut self,
+        token_type: &str,
+        contract_and_token_id: &ContractAndTokenId,
+    ) {
+        let token_type = token_type.to_owned();
+        let mut by_nft_token_type = self
+            .market
+            .by_nft_token_type
+            .get(&token_type)
+            .expect("No sale by nft_token_type");
+        by_nft_token_type.remove(contract_and_token_id);
+        if by_nft_token_type.is_empty() {
+            self.market.by_nft_token_type.remove(&token_type);
+        } else {
+            self.market
+                .by_nft_token_type
+                .insert(&token_type, &by_nft_token_type);
+        }
+        self.market
+            .sale_ids_by_token_type
+            .remove(&(token_type, contract_and_token_id.clone()));
     }
 
     pub(crate) fn internal_remove_bid(
@@ -95,7 +389,7 @@ impl Market {
                         .expect("No token")
                         .remove(index);
                 };
-                self.market.sales.insert(&contract_and_token_id, &sale);
+                self.internal_insert_sale(&contract_and_token_id, &sale);
                 //break; // shouldn't allow bids with equal price 
                 return Some((*bid_from_vec).clone());
             };
@@ -103,3 +397,51 @@ impl Market {
         None
     }
 }
+
+fn bidders(bids: Option<&Bids>) -> HashSet<AccountId> {
+    bids.into_iter()
+        .flat_map(|bids| bids.values())
+        .flatten()
+        .map(|bid| bid.owner_id.clone())
+        .collect()
+}
+
+// Price indexes are keyed by `prefix||ft_token_id` and order `(price, id)` pairs
+fn update_price_index(
+    index: &mut LookupMap<String, TreeMap<(u128, String), ()>>,
+    prefix: &str,
+    id: &str,
+    old: Option<&SaleConditions>,
+    new: Option<&SaleConditions>,
+    storage_key: impl Fn(CryptoHash) -> StorageKey,
+) {
+    if let Some(old) = old {
+        for (ft_token_id, price) in old {
+            if new.and_then(|new| new.get(ft_token_id)) == Some(price) {
+                continue;
+            }
+            let index_id = format!("{}{}{}", prefix, DELIMETER, ft_token_id);
+            let mut by_price = index.get(&index_id).expect("No sale by price");
+            by_price.remove(&(price.0, id.to_string()));
+            if by_price.is_empty() {
+                index.remove(&index_id);
+            } else {
+                index.insert(&index_id, &by_price);
+            }
+        }
+    }
+    if let Some(new) = new {
+        for (ft_token_id, price) in new {
+            let index_id = format!("{}{}{}", prefix, DELIMETER, ft_token_id);
+            let mut by_price = index.get(&index_id).unwrap_or_else(|| {
+                TreeMap::new(
+                    storage_key(env::sha256_array(index_id.as_bytes()))
+                        .try_to_vec()
+                        .unwrap(),
+                )
+            });
+            by_price.insert(&(price.0, id.to_string()), &());
+            index.insert(&index_id, &by_price);
+        }
+    }
+}
@@ -1,12 +1,17 @@
 mod auction;
 mod auction_views;
+mod batch;
 mod bid;
+mod bid_views;
 mod common;
 mod fee;
 mod inner;
 mod market_core;
+mod moderation;
+mod price_history;
 mod sale;
 mod sale_views;
+mod stats;
 mod token;
 
 mod hack; // TODO: remove
@@ -17,9 +22,14 @@ use crate::sale::{Sale, SaleConditions, TokenType,
     ContractAndTokenId, FungibleTokenId};
 use crate::auction::Auction;
 pub use crate::sale::{SaleJson, BID_HISTORY_LENGTH_DEFAULT};
-pub use crate::market_core::{ArgsKind, SaleArgs, AuctionArgs};
-pub use crate::auction::{AuctionJson, EXTENSION_DURATION};
+pub use crate::market_core::{ArgsKind, SaleArgs, SaleUpdateArgs, AuctionArgs};
+pub use crate::auction::{
+    AuctionIdJson, AuctionJson, AuctionStatus, KeeperBounty, Relist, EXTENSION_DURATION,
+};
 pub use crate::fee::{Fees, PAYOUT_TOTAL_VALUE, PROTOCOL_FEE};
+pub use crate::price_history::{PriceRecord, PriceStats, PRICE_HISTORY_LENGTH};
+pub use crate::stats::CollectionStats;
+pub use crate::common::Page;
 
 const STORAGE_PER_SALE: u128 = 1000 * STORAGE_PRICE_PER_BYTE;
 
@@ -38,21 +48,91 @@ pub enum StorageKey {
     OriginFees,
     Auctions,
     AuctionId,
+    Moderators,
+    BlockedAccounts,
+    ByAllowedBuyer,
+    ByAllowedBuyerInner { account_id_hash: CryptoHash },
+    AuctionsByOwnerId,
+    AuctionsByOwnerIdInner { account_id_hash: CryptoHash },
+    Relists,
+    PriceHistoryByToken,
+    PriceHistoryByNFTContractId,
+    PriceHistoryByNFTTokenType,
+    StatsByNFTContractId,
+    StatsByNFTTokenType,
+    SalesByPrice,
+    SalesByPriceInner { contract_and_ft_hash: CryptoHash },
+    TokenTypeSalesByPrice,
+    TokenTypeSalesByPriceInner { token_type_and_ft_hash: CryptoHash },
+    AuctionsByEnd,
+    SalesByBidder,
+    SalesByBidderInner { account_id_hash: CryptoHash },
+    AuctionsByBidder,
+    AuctionsByBidderInner { account_id_hash: CryptoHash },
+    SaleIds,
+    SaleIdsByOwnerId,
+    AuctionIds,
+    RelistsByOwnerId,
+    SaleIdsByTokenType,
+    SaleIdsByBidder,
+    AuctionIdsByBidder,
+    AuctionsByAllowedBuyer,
+    AuctionsByAllowedBuyerInner { account_id_hash: CryptoHash },
+    AuctionIdsByAllowedBuyer,
 }
 
 #[derive(BorshDeserialize, BorshSerialize, PanicOnDefault)]
 pub struct MarketSales {
     pub owner_id: AccountId,
     pub sales: UnorderedMap<ContractAndTokenId, Sale>,
+    // Ordered keys of `sales`, `by_owner_id`, `by_nft_token_type` and `sales_by_bidder` for cursor pagination
+    pub sale_ids: TreeMap<ContractAndTokenId, ()>,
+    pub sale_ids_by_owner_id: TreeMap<(AccountId, ContractAndTokenId), ()>,
+    pub sale_ids_by_token_type: TreeMap<(String, ContractAndTokenId), ()>,
+    pub sale_ids_by_bidder: TreeMap<(AccountId, ContractAndTokenId), ()>,
     pub by_owner_id: LookupMap<AccountId, UnorderedSet<ContractAndTokenId>>,
     pub by_nft_contract_id: LookupMap<AccountId, UnorderedSet<TokenId>>,
     pub by_nft_token_type: LookupMap<String, UnorderedSet<ContractAndTokenId>>,
+    pub by_allowed_buyer: LookupMap<AccountId, UnorderedSet<ContractAndTokenId>>,
+    // Sales where the account has at least one bid
+    pub sales_by_bidder: LookupMap<AccountId, UnorderedSet<ContractAndTokenId>>,
+    // Sales of the NFT contract in the ft token ordered by price, the key is `nft_contract_id||ft_token_id`
+    pub sales_by_price: LookupMap<String, TreeMap<(u128, TokenId), ()>>,
+    // Sales of the token type in the ft token ordered by price, the key is `token_type||ft_token_id`
+    pub token_type_sales_by_price: LookupMap<String, TreeMap<(u128, ContractAndTokenId), ()>>,
     pub ft_token_ids: UnorderedSet<FungibleTokenId>,
     pub storage_deposits: LookupMap<AccountId, Balance>,
     pub bid_history_length: u8,
 
     pub auctions: UnorderedMap<u128, Auction>,
+    pub auction_ids: TreeMap<u128, ()>,
+    pub auctions_by_owner_id: LookupMap<AccountId, UnorderedSet<u128>>,
+    // Auctions ordered by the end time, the key is `(end, auction_id)`
+    pub auctions_by_end: TreeMap<(u64, u128), ()>,
+    // Auctions where the account has the current bid
+    pub auctions_by_bidder: LookupMap<AccountId, UnorderedSet<u128>>,
+    // Ordered keys of `auctions_by_bidder` for cursor pagination
+    pub auction_ids_by_bidder: TreeMap<(AccountId, u128), ()>,
+    // Private auctions reserved for the account
+    pub auctions_by_allowed_buyer: LookupMap<AccountId, UnorderedSet<u128>>,
+    // Ordered keys of `auctions_by_allowed_buyer` for cursor pagination
+    pub auction_ids_by_allowed_buyer: TreeMap<(AccountId, u128), ()>,
     pub next_auction_id: u128,
+    pub relists: LookupMap<ContractAndTokenId, Relist>,
+    // Number of relist args the account pays storage for, both of the current bids and stored ones
+    pub relists_by_owner_id: LookupMap<AccountId, u64>,
+
+    pub price_history_by_token: LookupMap<ContractAndTokenId, Vec<PriceRecord>>,
+    pub price_history_by_nft_contract_id: LookupMap<AccountId, Vec<PriceRecord>>,
+    pub price_history_by_nft_token_type: LookupMap<String, Vec<PriceRecord>>,
+
+    pub stats_by_nft_contract_id: LookupMap<AccountId, CollectionStats>,
+    pub stats_by_nft_token_type: LookupMap<String, CollectionStats>,
+
+    pub keeper_bounty: Option<KeeperBounty>,
+
+    pub moderators: LookupSet<AccountId>,
+    pub blocked_accounts: LookupSet<AccountId>,
 }
 
 #[near_bindgen]
@@ -73,14 +153,41 @@ impl Market {
         let market = MarketSales {
             owner_id,
             sales: UnorderedMap::new(StorageKey::Sales),
+            sale_ids: TreeMap::new(StorageKey::SaleIds),
+            sale_ids_by_owner_id: TreeMap::new(StorageKey::SaleIdsByOwnerId),
+            sale_ids_by_token_type: TreeMap::new(StorageKey::SaleIdsByTokenType),
+            sale_ids_by_bidder: TreeMap::new(StorageKey::SaleIdsByBidder),
             by_owner_id: LookupMap::new(StorageKey::ByOwnerId),
             by_nft_contract_id: LookupMap::new(StorageKey::ByNFTContractId),
             by_nft_token_type: LookupMap::new(StorageKey::ByNFTTokenType),
+            by_allowed_buyer: LookupMap::new(StorageKey::ByAllowedBuyer),
+            sales_by_bidder: LookupMap::new(StorageKey::SalesByBidder),
+            sales_by_price: LookupMap::new(StorageKey::SalesByPrice),
+            token_type_sales_by_price: LookupMap::new(StorageKey::TokenTypeSalesByPrice),
             ft_token_ids: tokens,
             storage_deposits: LookupMap::new(StorageKey::StorageDeposits),
             bid_history_length: BID_HISTORY_LENGTH_DEFAULT,
             auctions: UnorderedMap::new(StorageKey::Auctions),
+            auction_ids: TreeMap::new(StorageKey::AuctionIds),
+            auctions_by_owner_id: LookupMap::new(StorageKey::AuctionsByOwnerId),
+            auctions_by_end: TreeMap::new(StorageKey::AuctionsByEnd),
+            auctions_by_bidder: LookupMap::new(StorageKey::AuctionsByBidder),
+            auction_ids_by_bidder: TreeMap::new(StorageKey::AuctionIdsByBidder),
+            auctions_by_allowed_buyer: LookupMap::new(StorageKey::AuctionsByAllowedBuyer),
+            auction_ids_by_allowed_buyer: TreeMap::new(StorageKey::AuctionIdsByAllowedBuyer),
+            relists: LookupMap::new(StorageKey::Relists),
+            relists_by_owner_id: LookupMap::new(StorageKey::RelistsByOwnerId),
+            price_history_by_token: LookupMap::new(StorageKey::PriceHistoryByToken),
+            price_history_by_nft_contract_id: LookupMap::new(
+                StorageKey::PriceHistoryByNFTContractId,
+            ),
+            price_history_by_nft_token_type: LookupMap::new(StorageKey::PriceHistoryByNFTTokenType),
+            stats_by_nft_contract_id: LookupMap::new(StorageKey::StatsByNFTContractId),
+            stats_by_nft_token_type: LookupMap::new(StorageKey::StatsByNFTTokenType),
             next_auction_id: 0,
+            keeper_bounty: None,
+            moderators: LookupSet::new(StorageKey::Moderators),
+            blocked_accounts: LookupSet::new(StorageKey::BlockedAccounts),
         };
         Self {
             non_fungible_token_account_ids,
@@ -93,8 +200,7 @@ impl Market {
         assert_one_yocto();
         let owner_id = env::predecessor_account_id();
         let mut amount = self.market.storage_deposits.remove(&owner_id).unwrap_or(0);
-        let sales = self.market.by_owner_id.get(&owner_id);
-        let len = sales.map(|s| s.len()).unwrap_or_default();
+        let len = self.internal_supply_by_owner_id(&owner_id);
         let diff = u128::from(len) * STORAGE_PER_SALE;
         amount -= diff;
         if amount > 0 {
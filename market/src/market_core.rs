@@ -1,6 +1,7 @@
 use near_sdk::serde_json::json;
 use crate::*;
 use crate::bid::Origins;
+use crate::sale::DELIMETER;
 
 
 pub trait NonFungibleTokenApprovalReceiver {
@@ -23,6 +24,32 @@ pub struct SaleArgs {
     pub end: Option<U64>,
 
     pub origins: Option<Origins>,
+    pub allowed_buyers: Option<Vec<AccountId>>,
+    // If true, after the end anyone can call `finish_sale` to execute the highest valid bid
+    pub settle_at_end: Option<bool>,
+}
+
+// Partial `SaleArgs` for `update_sale`, fields that are not specified stay unchanged
+#[derive(Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SaleUpdateArgs {
+    // Replaces all sale conditions, bids with removed ft tokens are refunded
+    pub sale_conditions: Option<SaleConditions>,
+    // `null` removes the token type, missing field keeps it
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub token_type: Option<TokenType>,
+    pub end: Option<U64>,
+    // Added to the existing origins
+    pub origins: Option<Origins>,
+}
+
+// Distinguishes between a missing field and an explicit `null`
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: near_sdk::serde::Deserializer<'de>,
+{
+    Deserialize::deserialize(deserializer).map(Some)
 }
 
 #[derive(Serialize, Deserialize)]
@@ -37,6 +64,7 @@ pub struct AuctionArgs {
     pub buy_out_price: Option<U128>,
 
     pub origins: Option<Origins>,
+    pub allowed_buyers: Option<Vec<AccountId>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -65,13 +93,21 @@ impl NonFungibleTokenApprovalReceiver for Market {
             "nft_on_approve should only be called via cross-contract call"
         );
         require!(owner_id == signer_id, "owner_id should be signer_id");
+        self.assert_not_blocked(&owner_id);
+
+        // Relist args of the token are used or replaced by the new listing
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, DELIMETER, token_id);
+        let relist = self.market.relists.remove(&contract_and_token_id);
+        if let Some(relist) = relist.as_ref() {
+            self.internal_remove_relist_owner(&relist.owner_id);
+        }
 
         // check that the signer's storage is enough to cover one more sale
 
         let storage_amount = self.storage_amount().0;
         let owner_paid_storage = self.market.storage_deposits.get(&signer_id).unwrap_or(0);
         let signer_storage_required =
-            (self.get_supply_by_owner_id(signer_id).0 + 1) as u128 * storage_amount;
+            (self.internal_supply_by_owner_id(&signer_id) + 1) as u128 * storage_amount;
         assert!(
             owner_paid_storage >= signer_storage_required,
             "Insufficient storage paid: {}, for {} sales at {} rate of per sale",
@@ -81,7 +117,15 @@ impl NonFungibleTokenApprovalReceiver for Market {
         );
 
         // Parse the msg to find Sale or Auction arguments
-
+        // An empty msg lists the token with the relist args of the auction winner
+        let msg = if msg.is_empty() {
+            let relist = relist
+                .filter(|relist| relist.owner_id == owner_id)
+                .unwrap_or_else(|| env::panic_str("No relist args for the token"));
+            relist.msg
+        } else {
+            msg
+        };
         let args: ArgsKind = near_sdk::serde_json::from_str(&msg).expect("Not valid args");
         match args {
             ArgsKind::Sale(sale_args) => {
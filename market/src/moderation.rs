@@ -0,0 +1,106 @@
+use near_sdk::serde_json::json;
+
+use crate::*;
+
+#[near_bindgen]
+impl Market {
+    pub fn add_moderator(&mut self, account_id: AccountId) -> bool {
+        require!(
+            env::predecessor_account_id() == self.market.owner_id,
+            "only owner can add moderators"
+        );
+        self.market.moderators.insert(&account_id)
+    }
+
+    pub fn remove_moderator(&mut self, account_id: AccountId) -> bool {
+        require!(
+            env::predecessor_account_id() == self.market.owner_id,
+            "only owner can remove moderators"
+        );
+        self.market.moderators.remove(&account_id)
+    }
+
+    pub fn is_moderator(&self, account_id: AccountId) -> bool {
+        account_id == self.market.owner_id || self.market.moderators.contains(&account_id)
+    }
+
+    pub fn block_account(&mut self, account_id: AccountId) -> bool {
+        self.assert_moderator();
+        self.market.blocked_accounts.insert(&account_id)
+    }
+
+    pub fn unblock_account(&mut self, account_id: AccountId) -> bool {
+        self.assert_moderator();
+        self.market.blocked_accounts.remove(&account_id)
+    }
+
+    pub fn is_blocked(&self, account_id: AccountId) -> bool {
+        self.market.blocked_accounts.contains(&account_id)
+    }
+
+    // Removes any sale regardless of its owner and time limits
+    // Refunds all bids
+    #[payable]
+    pub fn moderator_remove_sale(&mut self, nft_contract_id: AccountId, token_id: TokenId) {
+        assert_one_yocto();
+        self.assert_moderator();
+        let sale = self.internal_remove_sale(nft_contract_id, token_id);
+        self.refund_all_bids(&sale.bids);
+        env::log_str(
+            &json!({
+                "type": "moderator_remove_sale",
+                "params": {
+                    "moderator_id": env::predecessor_account_id(),
+                    "owner_id": sale.owner_id,
+                    "nft_contract_id": sale.nft_contract_id,
+                    "token_id": sale.token_id,
+                }
+            })
+            .to_string(),
+        );
+    }
+
+    // Cancels any auction, even if it already has a bid
+    // Refunds the bid
+    #[payable]
+    pub fn moderator_cancel_auction(&mut self, auction_id: U128) {
+        assert_one_yocto();
+        self.assert_moderator();
+        let auction = self.internal_remove_auction(auction_id.into());
+        if let Some(bid) = auction.bid {
+            if auction.relist.is_some() {
+                self.internal_remove_relist_owner(&bid.owner_id);
+            }
+            self.refund_bid(auction.ft_token_id, bid.owner_id, bid.price);
+        }
+        env::log_str(
+            &json!({
+                "type": "moderator_cancel_auction",
+                "params": {
+                    "moderator_id": env::predecessor_account_id(),
+                    "auction_id": auction_id,
+                    "owner_id": auction.owner_id,
+                    "nft_contract_id": auction.nft_contract_id,
+                    "token_id": auction.token_id,
+                }
+            })
+            .to_string(),
+        );
+    }
+}
+
+impl Market {
+    pub(crate) fn assert_moderator(&self) {
+        require!(
+            self.is_moderator(env::predecessor_account_id()),
+            "Only moderators can call this method"
+        );
+    }
+
+    pub(crate) fn assert_not_blocked(&self, account_id: &AccountId) {
+        require!(
+            !self.market.blocked_accounts.contains(account_id),
+            format!("Account {} is blocked", account_id)
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use crate::common::*;
+use crate::*;
+
+use crate::sale::DELIMETER;
+
+// Number of the latest completed sales kept per token, NFT contract and token type
+pub const PRICE_HISTORY_LENGTH: usize = 50;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceRecord {
+    pub token_id: TokenId,
+    pub ft_token_id: FungibleTokenId,
+    pub price: U128,
+    pub timestamp: U64,
+}
+
+// Aggregates over the completed sales in one ft token
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceStats {
+    pub last_price: Option<U128>,
+    pub floor_price: Option<U128>,
+    pub volume: U128,
+    pub count: u64,
+    // Each price is weighted by the time it stayed the last one within the window
+    pub time_weighted_price: Option<U128>,
+}
+
+impl Default for PriceStats {
+    fn default() -> Self {
+        Self {
+            last_price: None,
+            floor_price: None,
+            volume: U128(0),
+            count: 0,
+            time_weighted_price: None,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Market {
+    pub fn get_token_price_history(
+        &self,
+        nft_contract_id: AccountId,
+        token_id: TokenId,
+    ) -> Vec<PriceRecord> {
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, DELIMETER, token_id);
+        self.market
+            .price_history_by_token
+            .get(&contract_and_token_id)
+            .unwrap_or_default()
+    }
+
+    pub fn get_contract_price_history(&self, nft_contract_id: AccountId) -> Vec<PriceRecord> {
+        self.market
+            .price_history_by_nft_contract_id
+            .get(&nft_contract_id)
+            .unwrap_or_default()
+    }
+
+    pub fn get_token_type_price_history(&self, token_type: String) -> Vec<PriceRecord> {
+        self.market
+            .price_history_by_nft_token_type
+            .get(&token_type)
+            .unwrap_or_default()
+    }
+
+    // `window` is the duration in nanoseconds before the current block, the whole history if not set
+    pub fn get_token_price_stats(
+        &self,
+        nft_contract_id: AccountId,
+        token_id: TokenId,
+        ft_token_id: FungibleTokenId,
+        window: Option<U64>,
+    ) -> PriceStats {
+        price_stats(
+            &self.get_token_price_history(nft_contract_id, token_id),
+            &ft_token_id,
+            window.map(u64::from),
+        )
+    }
+
+    pub fn get_contract_price_stats(
+        &self,
+        nft_contract_id: AccountId,
+        ft_token_id: FungibleTokenId,
+        window: Option<U64>,
+    ) -> PriceStats {
+        price_stats(
+            &self.get_contract_price_history(nft_contract_id),
+            &ft_token_id,
+            window.map(u64::from),
+        )
+    }
+
+    pub fn get_token_type_price_stats(
+        &self,
+        token_type: String,
+        ft_token_id: FungibleTokenId,
+        window: Option<U64>,
+    ) -> PriceStats {
+        price_stats(
+            &self.get_token_type_price_history(token_type),
+            &ft_token_id,
+            window.map(u64::from),
+        )
+    }
+}
+
+impl Market {
+    // Called after the token is transferred and the payouts are valid
+    // The `price` is the sale price, without the fees paid by the buyer
+    pub(crate) fn internal_record_price(
+        &mut self,
+        nft_contract_id: &AccountId,
+        token_id: &TokenId,
+        token_type: Option<&String>,
+        ft_token_id: &FungibleTokenId,
+        price: U128,
+    ) {
+        let record = PriceRecord {
+            token_id: token_id.clone(),
+            ft_token_id: ft_token_id.clone(),
+            price,
+            timestamp: env::block_timestamp().into(),
+        };
+        self.internal_add_volume(nft_contract_id, token_type, ft_token_id, price);
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, DELIMETER, token_id);
+        push_record(
+            &mut self.market.price_history_by_token,
+            &contract_and_token_id,
+            record.clone(),
+        );
+        if let Some(token_type) = token_type {
+            push_record(
+                &mut self.market.price_history_by_nft_token_type,
+                token_type,
+                record.clone(),
+            );
+        }
+        push_record(
+            &mut self.market.price_history_by_nft_contract_id,
+            nft_contract_id,
+            record,
+        );
+    }
+}
+
+fn push_record<K: BorshSerialize>(
+    histories: &mut LookupMap<K, Vec<PriceRecord>>,
+    key: &K,
+    record: PriceRecord,
+) {
+    let mut history = histories.get(key).unwrap_or_default();
+    if history.len() >= PRICE_HISTORY_LENGTH {
+        history.remove(0);
+    }
+    history.push(record);
+    histories.insert(key, &history);
+}
+
+fn price_stats(
+    history: &[PriceRecord],
+    ft_token_id: &FungibleTokenId,
+    window: Option<u64>,
+) -> PriceStats {
+    let now = env::block_timestamp();
+    let records: Vec<&PriceRecord> = history
+        .iter()
+        .filter(|record| &record.ft_token_id == ft_token_id)
+        .collect();
+    let window_start = match (window, records.first()) {
+        (Some(window), _) => now.saturating_sub(window),
+        (None, Some(first)) => first.timestamp.0,
+        (None, None) => return PriceStats::default(),
+    };
+
+    let mut stats = PriceStats::default();
+    // Prices with the time they stayed the last one within the window
+    let mut weighted_prices = vec![];
+    let mut total_time = 0;
+    for (i, record) in records.iter().enumerate() {
+        let until = records
+            .get(i + 1)
+            .map(|next| next.timestamp.0)
+            .unwrap_or(now);
+        // The price stays the last one until the next sale, including the time before the window
+        if until > window_start {
+            let duration = until - std::cmp::max(record.timestamp.0, window_start);
+            weighted_prices.push((record.price.0, duration as u128));
+            total_time += duration as u128;
+        }
+        if record.timestamp.0 < window_start {
+            continue;
+        }
+        stats.count += 1;
+        stats.volume.0 += record.price.0;
+        stats.floor_price = Some(match stats.floor_price {
+            Some(floor) if floor.0 <= record.price.0 => floor,
+            _ => record.price,
+        });
+    }
+    stats.last_price = records.last().map(|record| record.price);
+    // Each price is divided by the total time before it's multiplied by its duration in nanoseconds
+    stats.time_weighted_price = if total_time > 0 {
+        Some(U128(
+            weighted_prices
+                .into_iter()
+                .map(|(price, duration)| mul_div(price, duration, total_time))
+                .sum(),
+        ))
+    } else {
+        stats.last_price
+    };
+    stats
+}
+
+// `value * numerator / denominator` rounded down, `numerator` should not exceed `denominator`
+fn mul_div(value: u128, numerator: u128, denominator: u128) -> u128 {
+    value / denominator * numerator + value % denominator * numerator / denominator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+
+    const ONE_NEAR: u128 = 1_000_000_000_000_000_000_000_000;
+    const DAY: u64 = 24 * 60 * 60 * NANOS_PER_SEC;
+
+    fn record(price: u128, timestamp: u64) -> PriceRecord {
+        PriceRecord {
+            token_id: "1:1".to_string(),
+            ft_token_id: "near".parse().unwrap(),
+            price: U128(price),
+            timestamp: U64(timestamp),
+        }
+    }
+
+    #[test]
+    fn time_weighted_price_of_days() {
+        let start = 1_650_000_000 * NANOS_PER_SEC;
+        testing_env!(VMContextBuilder::new()
+            .block_timestamp(start + 8 * DAY)
+            .build());
+        let history = vec![record(ONE_NEAR, start), record(2 * ONE_NEAR, start + 4 * DAY)];
+        let near = "near".parse().unwrap();
+
+        // Each price stays the last one for 4 days
+        let stats = price_stats(&history, &near, None);
+        assert_eq!(stats.time_weighted_price, Some(U128(3 * ONE_NEAR / 2)));
+        assert_eq!(stats.volume, U128(3 * ONE_NEAR));
+
+        // 3 days of the first price are within the window
+        let stats = price_stats(&history, &near, Some(7 * DAY));
+        assert_eq!(stats.time_weighted_price, Some(U128(11 * ONE_NEAR / 7)));
+        assert_eq!(stats.count, 1);
+    }
+}
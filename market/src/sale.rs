@@ -5,13 +5,13 @@ use near_sdk::ext_contract;
 use near_sdk::serde_json::json;
 use near_sdk::{promise_result_as_success, Gas};
 
-use crate::fee::calculate_price_with_fees;
-use crate::market_core::SaleArgs;
+use crate::fee::{calculate_price_with_fees, checked_payout};
+use crate::market_core::{SaleArgs, SaleUpdateArgs};
 use crate::*;
 use common::*;
 use near_contract_standards::non_fungible_token::hash_account_id;
 
-use bid::{Bids, Origins};
+use bid::{bids_count, Bid, Bids, Origins};
 pub type TokenSeriesId = String;
 
 pub const GAS_FOR_FT_TRANSFER: Gas = Gas(5_000_000_000_000);
@@ -50,6 +50,8 @@ pub struct Sale {
     pub end: Option<u64>,
 
     pub origins: Origins,
+    pub allowed_buyers: Option<Vec<AccountId>>,
+    pub settle_at_end: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -66,6 +68,8 @@ pub struct SaleJson {
     pub start: Option<U64>,
     pub end: Option<U64>,
     pub origins: Origins,
+    pub allowed_buyers: Option<Vec<AccountId>>,
+    pub settle_at_end: bool,
 }
 
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -91,6 +95,35 @@ impl Sale {
         res
     }
 
+    pub fn is_allowed_buyer(&self, buyer_id: &AccountId) -> bool {
+        self.allowed_buyers
+            .as_ref()
+            .map(|buyers| buyers.contains(buyer_id))
+            .unwrap_or(true)
+    }
+
+    // Returns the highest bid which is still valid at the end of the sale
+    // Settlement sales have only one ft token, so the last valid bid is the highest one
+    pub fn winning_bid(&self) -> Option<(FungibleTokenId, Bid)> {
+        let end = self.end?;
+        self.bids.iter().find_map(|(ft_token_id, bids)| {
+            bids.iter()
+                .rev()
+                .find(|bid| bid.start.0 < end && bid.end.map(|e| e.0 >= end).unwrap_or(true))
+                .map(|bid| (ft_token_id.clone(), bid.clone()))
+        })
+    }
+
+    // The bid `finish_sale` is going to execute once a sale with settlement at the end has ended.
+    // It can't be cancelled or refunded as expired before the sale is finished
+    pub fn settlement_bid(&self) -> Option<(FungibleTokenId, Bid)> {
+        let end = self.end?;
+        if !self.settle_at_end || env::block_timestamp() < end {
+            return None;
+        }
+        self.winning_bid()
+    }
+
     pub fn extend(&mut self, time: u64) -> bool {
         if let Some(end) = self.end {
             self.end = Some(end + time);
@@ -124,7 +157,19 @@ impl Market {
             start,
             end,
             origins,
+            allowed_buyers,
+            settle_at_end,
         } = args;
+        let settle_at_end = settle_at_end.unwrap_or(false);
+        // Each buyer is indexed once, so it can be removed from `by_allowed_buyer` once
+        let allowed_buyers = allowed_buyers.map(dedup_accounts);
+        if settle_at_end {
+            require!(end.is_some(), "Sale with settlement at the end should have the end time");
+            require!(
+                sale_conditions.len() == 1,
+                "Sale with settlement at the end should have exactly one ft token"
+            );
+        }
 
         // check that the offered ft token is supported
 
@@ -155,8 +200,10 @@ impl Market {
             start: Some(start),
             end: end.map(|e| e.into()),
             origins: origins.unwrap_or_default(),
+            allowed_buyers: allowed_buyers.clone(),
+            settle_at_end,
         };
-        self.market.sales.insert(&contract_and_token_id, &sale);
+        self.internal_insert_sale(&contract_and_token_id, &sale);
 
         // extra for views
 
@@ -176,13 +223,18 @@ impl Market {
             .storage_deposits
             .get(&env::signer_account_id())
             .unwrap_or(0);
-        let owner_occupied_storage = u128::from(by_owner_id.len()) * STORAGE_PER_SALE;
+        let owner_occupied_storage =
+            u128::from(self.internal_supply_by_owner_id(&owner_id)) * STORAGE_PER_SALE;
         assert!(
             owner_paid_storage > owner_occupied_storage,
             "User has more sales than storage paid"
         );
         by_owner_id.insert(&contract_and_token_id);
         self.market.by_owner_id.insert(&owner_id, &by_owner_id);
+        self.market.sale_ids.insert(&contract_and_token_id, &());
+        self.market
+            .sale_ids_by_owner_id
+            .insert(&(owner_id.clone(), contract_and_token_id.clone()), &());
 
         let mut by_nft_contract_id = self
             .market
@@ -207,27 +259,44 @@ impl Market {
                 token_id.contains(token_type.as_str()),
                 "TokenType should be substr of TokenId"
             );
-            let mut by_nft_token_type = self
-                .market
-                .by_nft_token_type
-                .get(&token_type)
-                .unwrap_or_else(|| {
-                    UnorderedSet::new(
-                        StorageKey::ByNFTTokenTypeInner {
-                            token_type_hash: hash_account_id(&AccountId::new_unchecked(
-                                token_type.clone(),
-                            )),
-                        }
-                        .try_to_vec()
-                        .unwrap(),
-                    )
-                });
-            by_nft_token_type.insert(&contract_and_token_id);
-            self.market
-                .by_nft_token_type
-                .insert(&token_type, &by_nft_token_type);
+            self.internal_add_sale_by_token_type(&token_type, &contract_and_token_id);
+        }
+
+        if let Some(allowed_buyers) = allowed_buyers {
+            require!(!allowed_buyers.is_empty(), "allowed_buyers should not be empty");
+            for buyer_id in allowed_buyers {
+                let mut by_allowed_buyer = self
+                    .market
+                    .by_allowed_buyer
+                    .get(&buyer_id)
+                    .unwrap_or_else(|| {
+                        UnorderedSet::new(
+                            StorageKey::ByAllowedBuyerInner {
+                                account_id_hash: hash_account_id(&buyer_id),
+                            }
+                            .try_to_vec()
+                            .unwrap(),
+                        )
+                    });
+                by_allowed_buyer.insert(&contract_and_token_id);
+                self.market
+                    .by_allowed_buyer
+                    .insert(&buyer_id, &by_allowed_buyer);
+            }
         }
 
+        self.internal_update_price_index(
+            &sale.nft_contract_id,
+            &sale.token_id,
+            None,
+            Some(&sale.sale_conditions),
+        );
+        self.internal_update_sale_stats(
+            &sale.nft_contract_id,
+            &sale.token_id,
+            None,
+            Some((&sale.sale_conditions, sale.token_type.as_ref())),
+        );
         self.json_from_sale(sale)
     }
 
@@ -242,6 +311,11 @@ impl Market {
                 owner_id, sale.owner_id,
                 "Until the sale is finished, it can only be removed by the sale owner"
             );
+        } else if sale.settle_at_end {
+            require!(
+                sale.winning_bid().is_none(),
+                "The sale has a valid bid and should be settled with finish_sale"
+            );
         };
         self.refund_all_bids(&sale.bids);
     }
@@ -273,8 +347,138 @@ impl Market {
                 ft_token_id
             ));
         }
+        let old_sale_conditions = sale.sale_conditions.clone();
         sale.sale_conditions.insert(ft_token_id, price);
-        self.market.sales.insert(&contract_and_token_id, &sale);
+        self.internal_insert_sale(&contract_and_token_id, &sale);
+        self.internal_update_price_index(
+            &sale.nft_contract_id,
+            &sale.token_id,
+            Some(&old_sale_conditions),
+            Some(&sale.sale_conditions),
+        );
+        self.internal_update_sale_stats(
+            &sale.nft_contract_id,
+            &sale.token_id,
+            Some((&old_sale_conditions, sale.token_type.as_ref())),
+            Some((&sale.sale_conditions, sale.token_type.as_ref())),
+        );
+    }
+
+    // Updates the sale parameters given in `args`
+    // While there are bids, `end` can't be set before the start of any of them
+    // and origins can't be changed, as the bidders have already agreed to them
+    #[payable]
+    pub fn update_sale(
+        &mut self,
+        nft_contract_id: AccountId,
+        token_id: String,
+        args: SaleUpdateArgs,
+    ) -> SaleJson {
+        assert_one_yocto();
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, DELIMETER, token_id);
+        let mut sale = self
+            .market
+            .sales
+            .get(&contract_and_token_id)
+            .expect("No sale");
+        assert_eq!(
+            env::predecessor_account_id(),
+            sale.owner_id,
+            "Must be sale owner"
+        );
+        let old_sale_conditions = sale.sale_conditions.clone();
+        let old_token_type = sale.token_type.clone();
+        let SaleUpdateArgs {
+            sale_conditions,
+            token_type,
+            end,
+            origins,
+        } = args;
+
+        if let Some(sale_conditions) = sale_conditions {
+            require!(!sale_conditions.is_empty(), "sale_conditions should not be empty");
+            require!(
+                !sale.settle_at_end || sale_conditions.len() == 1,
+                "Sale with settlement at the end should have exactly one ft token"
+            );
+            for ft_token_id in sale_conditions.keys() {
+                if !self.market.ft_token_ids.contains(ft_token_id) {
+                    env::panic_str(&format!(
+                        "Token {} not supported by this market",
+                        ft_token_id
+                    ));
+                }
+            }
+            // Bids in the tokens which are no longer accepted are refunded
+            let removed_fts: Vec<FungibleTokenId> = sale
+                .bids
+                .keys()
+                .filter(|ft_token_id| !sale_conditions.contains_key(ft_token_id))
+                .cloned()
+                .collect();
+            for ft_token_id in removed_fts {
+                let bids = sale.bids.remove(&ft_token_id).unwrap_or_default();
+                for bid in bids {
+                    self.refund_bid(ft_token_id.clone(), bid.owner_id, bid.price);
+                }
+            }
+            sale.sale_conditions = sale_conditions;
+        }
+
+        if let Some(end) = end {
+            let end = end.0;
+            require!(
+                sale.settlement_bid().is_none(),
+                "Can't change the end of the ended sale until it's settled"
+            );
+            require!(end > env::block_timestamp(), "End time should be in the future");
+            if let Some(start) = sale.start {
+                require!(end > start, "End time should be after the start time");
+            }
+            for bid in sale.bids.values().flatten() {
+                require!(
+                    end >= bid.start.0,
+                    "Can't set the end before the time of an active bid"
+                );
+            }
+            sale.end = Some(end);
+        }
+
+        if let Some(origins) = origins {
+            require!(sale.bids.is_empty(), "Can't change origins while the sale has bids");
+            sale.origins.extend(origins);
+        }
+
+        if let Some(token_type) = token_type {
+            if let Some(ref new_token_type) = token_type {
+                assert!(
+                    token_id.contains(new_token_type.as_str()),
+                    "TokenType should be substr of TokenId"
+                );
+            }
+            if let Some(old_token_type) = sale.token_type.as_ref() {
+                self.internal_remove_sale_by_token_type(old_token_type, &contract_and_token_id);
+            }
+            if let Some(new_token_type) = token_type.as_ref() {
+                self.internal_add_sale_by_token_type(new_token_type, &contract_and_token_id);
+            }
+            sale.token_type = token_type;
+        }
+
+        self.internal_insert_sale(&contract_and_token_id, &sale);
+        self.internal_update_price_index(
+            &sale.nft_contract_id,
+            &sale.token_id,
+            Some(&old_sale_conditions),
+            Some(&sale.sale_conditions),
+        );
+        self.internal_update_sale_stats(
+            &sale.nft_contract_id,
+            &sale.token_id,
+            Some((&old_sale_conditions, old_token_type.as_ref())),
+            Some((&sale.sale_conditions, sale.token_type.as_ref())),
+        );
+        self.json_from_sale(sale)
     }
 
     // Offer to buy the nft
@@ -304,6 +508,11 @@ impl Market {
 
         let buyer_id = env::predecessor_account_id();
         require!(sale.owner_id != buyer_id, "Cannot bid on your own sale.");
+        self.assert_not_blocked(&buyer_id);
+        require!(
+            sale.is_allowed_buyer(&buyer_id),
+            "The sale is reserved for other buyers"
+        );
         let price = *sale
             .sale_conditions
             .get(&ft_token_id)
@@ -359,7 +568,7 @@ impl Market {
         let bids_for_token_id = sale.bids.remove(&ft_token_id).expect("No bids");
         let bid = &bids_for_token_id[bids_for_token_id.len() - 1];
         require!(bid.in_limits(), "Out of time limit of the bid");
-        self.market.sales.insert(&contract_and_token_id, &sale);
+        self.internal_insert_sale(&contract_and_token_id, &sale);
         // panics at `self.internal_remove_sale` and reverts above if predecessor is not sale.owner_id
         self.process_purchase(
             contract_id,
@@ -371,6 +580,85 @@ impl Market {
         );
     }
 
+    // Accepts the bid made by `bidder_id` with the given `price`
+    // All other bids of the sale are refunded after the purchase
+    pub fn accept_bid(
+        &mut self,
+        nft_contract_id: AccountId,
+        token_id: String,
+        ft_token_id: AccountId,
+        bidder_id: AccountId,
+        price: U128,
+    ) {
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, DELIMETER, token_id);
+        let sale = self
+            .market
+            .sales
+            .get(&contract_and_token_id)
+            .expect("No sale");
+        require!(
+            env::predecessor_account_id() == sale.owner_id,
+            "Only the sale owner can accept a bid"
+        );
+        require!(
+            sale.in_limits(),
+            "Either the sale is finished or it hasn't started yet"
+        );
+        let bid = self
+            .internal_remove_bid(
+                nft_contract_id.clone(),
+                &ft_token_id,
+                token_id.clone(),
+                &bidder_id,
+                price,
+            )
+            .expect("No such bid");
+        require!(bid.in_limits(), "Out of time limit of the bid");
+        self.process_purchase(
+            nft_contract_id,
+            token_id,
+            ft_token_id,
+            bid.price,
+            bid.owner_id,
+            bid.origins,
+        );
+    }
+
+    // Finishes the sale with settlement at the end if it has reached its end
+    // Executes the highest valid bid, can be called by anyone
+    pub fn finish_sale(&mut self, nft_contract_id: AccountId, token_id: String) -> Promise {
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, DELIMETER, token_id);
+        let sale = self
+            .market
+            .sales
+            .get(&contract_and_token_id)
+            .expect("No sale");
+        require!(sale.settle_at_end, "The sale is not settled at the end");
+        let end = sale.end.expect("Sale with settlement at the end should have the end time");
+        require!(
+            env::block_timestamp() >= end,
+            "Sale can be finalized only after the end time"
+        );
+        let (ft_token_id, bid) = sale
+            .winning_bid()
+            .unwrap_or_else(|| env::panic_str("Can finalize only if there is a valid bid"));
+        self.internal_remove_bid(
+            nft_contract_id.clone(),
+            &ft_token_id,
+            token_id.clone(),
+            &bid.owner_id,
+            bid.price,
+        );
+        self.process_purchase(
+            nft_contract_id,
+            token_id,
+            ft_token_id,
+            bid.price,
+            bid.owner_id,
+            bid.origins,
+        )
+    }
+
     #[private]
     pub fn process_purchase(
         &mut self,
@@ -382,22 +670,19 @@ impl Market {
         origins: Origins,
     ) -> Promise {
         let sale = self.internal_remove_sale(nft_contract_id.clone(), token_id.clone());
-        let mut buyer = origins;
-        buyer.insert(env::current_account_id(), PROTOCOL_FEE as u32);
-        let mut seller_fee = HashMap::with_capacity(sale.origins.len() + 1);
-        seller_fee.extend(sale.origins.clone()); // TODO: dodge this clone
-        seller_fee.insert(env::current_account_id(), PROTOCOL_FEE as u32);
-        let fees = fee::Fees {
-            buyer,
-            seller: seller_fee,
-        };
+        let fees = Fees::new(origins, &sale.origins);
+        // The NFT contract rejects the transfer if its payout doesn't fit with the fees and refunds
+        let max_len_payout = fees
+            .max_len_payout(&sale.owner_id, bids_count(&sale.bids))
+            .unwrap_or_else(|| env::panic_str("Too many fees and bid refunds for the payout"));
+        // NFT contract pays royalties out of the price without the buyer fees
         ext_contract::nft_transfer_payout(
             buyer_id.clone(),
             token_id,
             sale.approval_id,
-            Some(near_sdk::serde_json::to_string(&fees).expect("Failed to sereailize")),
-            price,
-            10,
+            None,
+            fees.sale_price(price.0).into(),
+            max_len_payout,
             nft_contract_id,
             1,
             GAS_FOR_NFT_TRANSFER,
@@ -407,6 +692,7 @@ impl Market {
             buyer_id,
             sale,
             price,
+            fees,
             env::current_account_id(),
             NO_DEPOSIT,
             GAS_FOR_ROYALTIES,
@@ -416,6 +702,7 @@ impl Market {
     // self callback
     // If transfer of token succeded - count fees and transfer payouts
     // If failed - refund price to buyer
+    // Invalid payout of the NFT contract pays the whole sale price to the seller
     #[private]
     pub fn resolve_purchase(
         &mut self,
@@ -423,37 +710,18 @@ impl Market {
         buyer_id: AccountId,
         sale: Sale,
         price: U128,
+        fees: Fees,
     ) -> U128 {
-        // checking for payout information
-        let payout_option = promise_result_as_success().and_then(|value| {
-            // None means a bad payout from bad NFT contract
-            near_sdk::serde_json::from_slice::<Payout>(&value)
-                .ok()
-                .and_then(|payout| {
-                    // gas to do 10 FT transfers (and definitely 10 NEAR transfers)
-                    if payout.payout.len() + sale.bids.len() > 10 || payout.payout.is_empty() {
-                        env::log_str("Cannot have more than 10 royalties and sale.bids refunds");
-                        None
-                    } else {
-                        let mut remainder = price.0;
-                        for &value in payout.payout.values() {
-                            remainder = remainder.checked_sub(value.0)?;
-                        }
-                        if remainder <= 1 {
-                            Some(payout)
-                        } else {
-                            None
-                        }
-                    }
-                })
-        });
-        // is payout option valid?
-        let payout = if let Some(payout_option) = payout_option {
-            payout_option
+        let sale_price = fees.sale_price(price.0);
+        let value = if let Some(value) = promise_result_as_success() {
+            value
         } else {
+            // the token wasn't transferred, refund the buyer
             if ft_token_id == "near".parse().unwrap() {
                 Promise::new(buyer_id.clone()).transfer(u128::from(price));
             }
+            // the sale is already removed, so the rest of the bids can't be accepted anymore
+            self.refund_all_bids(&sale.bids);
             // leave function and return all FTs in ft_resolve_transfer
             env::log_str(
                 &json!({
@@ -471,8 +739,21 @@ impl Market {
             );
             return price;
         };
+        // The token is transferred, so the buyer is never refunded from here on
+        let max_len_payout = fees
+            .max_len_payout(&sale.owner_id, bids_count(&sale.bids))
+            .unwrap_or_default();
+        let mut payout = checked_payout(&value, &sale.owner_id, sale_price, max_len_payout);
+        fees.apply(&mut payout, &sale.owner_id, sale_price);
         // Going to payout everyone, first return all outstanding bids (accepted offer bid was already removed)
         self.refund_all_bids(&sale.bids); // TODO: maybe should do this outside of this call, to lower gas for this call
+        self.internal_record_price(
+            &sale.nft_contract_id,
+            &sale.token_id,
+            sale.token_type.as_ref(),
+            &ft_token_id,
+            sale_price.into(),
+        );
 
         // NEAR payouts
         if ft_token_id == "near".parse().unwrap() {
@@ -539,9 +820,20 @@ trait ExtSelf {
         buyer_id: AccountId,
         sale: Sale,
         price: U128,
+        fees: Fees,
     ) -> Promise;
 
-    fn resolve_finish_auction(&mut self, ft_token_id: AccountId, buyer_id: AccountId, price: U128);
+    fn resolve_finish_auction(
+        &mut self,
+        ft_token_id: AccountId,
+        buyer_id: AccountId,
+        price: U128,
+        owner_id: AccountId,
+        fees: Fees,
+        keeper_id: AccountId,
+        contract_and_token_id: ContractAndTokenId,
+        relist: Option<String>,
+    );
 
     fn resolve_mint(
         &mut self,
@@ -3,6 +3,7 @@ use crate::*;
 
 use crate::sale::{SaleJson, DELIMETER};
 use std::cmp::min;
+use std::ops::Bound;
 
 #[near_bindgen]
 impl Market {
@@ -27,6 +28,155 @@ impl Market {
             .collect()
     }
 
+    pub fn get_supply_by_price(&self, nft_contract_id: AccountId, ft_token_id: AccountId) -> U64 {
+        let index_id = format!("{}{}{}", nft_contract_id, DELIMETER, ft_token_id);
+        let by_price = self.market.sales_by_price.get(&index_id);
+        if let Some(by_price) = by_price {
+            U64(by_price.len())
+        } else {
+            U64(0)
+        }
+    }
+
+    // Sales of the NFT contract which can be bought in `ft_token_id`
+    // Sorted by the price in ascending order, or descending if `descending` is true
+    pub fn get_sales_by_price(
+        &self,
+        nft_contract_id: AccountId,
+        ft_token_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+        descending: Option<bool>,
+    ) -> Vec<SaleJson> {
+        let index_id = format!("{}{}{}", nft_contract_id, DELIMETER, ft_token_id);
+        let by_price = if let Some(by_price) = self.market.sales_by_price.get(&index_id) {
+            by_price
+        } else {
+            return vec![];
+        };
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        let keys: Vec<(u128, TokenId)> = if descending.unwrap_or(false) {
+            by_price
+                .iter_rev()
+                .skip(start_index as usize)
+                .take(limit)
+                .map(|(key, _)| key)
+                .collect()
+        } else {
+            by_price
+                .iter()
+                .skip(start_index as usize)
+                .take(limit)
+                .map(|(key, _)| key)
+                .collect()
+        };
+        keys.into_iter()
+            .map(|(_, token_id)| {
+                let contract_and_token_id =
+                    format!("{}{}{}", nft_contract_id, DELIMETER, token_id);
+                self.json_from_sale(self.market.sales.get(&contract_and_token_id).unwrap())
+            })
+            .collect()
+    }
+
+    // Cursor is the `price||token_id` of the last sale of the previous page
+    pub fn get_sales_by_price_page(
+        &self,
+        nft_contract_id: AccountId,
+        ft_token_id: AccountId,
+        cursor: Option<String>,
+        limit: Option<u64>,
+        descending: Option<bool>,
+    ) -> Page<SaleJson> {
+        let index_id = format!("{}{}{}", nft_contract_id, DELIMETER, ft_token_id);
+        let by_price = if let Some(by_price) = self.market.sales_by_price.get(&index_id) {
+            by_price
+        } else {
+            return Page {
+                items: vec![],
+                next_cursor: None,
+            };
+        };
+        let cursor = cursor.map(|cursor| {
+            let (price, token_id) = cursor
+                .split_once(DELIMETER)
+                .unwrap_or_else(|| env::panic_str("Invalid cursor"));
+            let price: u128 = price.parse().unwrap_or_else(|_| env::panic_str("Invalid cursor"));
+            (price, token_id.to_string())
+        });
+        let (keys, next_cursor) = match (cursor, descending.unwrap_or(false)) {
+            (Some(cursor), true) => paginate(by_price.iter_rev_from(cursor), limit),
+            (Some(cursor), false) => paginate(by_price.iter_from(cursor), limit),
+            (None, true) => paginate(by_price.iter_rev(), limit),
+            (None, false) => paginate(by_price.iter(), limit),
+        };
+        let prefix = format!("{}{}", nft_contract_id, DELIMETER);
+        self.sales_page(
+            keys.into_iter()
+                .map(|((_, token_id), _)| format!("{}{}", prefix, token_id)),
+            next_cursor.map(|((price, token_id), _)| format!("{}{}{}", price, DELIMETER, token_id)),
+        )
+    }
+
+    // Cursor is the `nft_contract_id||token_id` of the last sale of the previous page
+    pub fn get_sales_page(&self, cursor: Option<String>, limit: Option<u64>) -> Page<SaleJson> {
+        let (keys, next_cursor) = match cursor {
+            Some(cursor) => paginate(self.market.sale_ids.iter_from(cursor), limit),
+            None => paginate(self.market.sale_ids.iter(), limit),
+        };
+        self.sales_page(
+            keys.into_iter().map(|(key, _)| key),
+            next_cursor.map(|(key, _)| key),
+        )
+    }
+
+    pub fn get_sales_by_owner_id_page(
+        &self,
+        account_id: AccountId,
+        cursor: Option<String>,
+        limit: Option<u64>,
+    ) -> Page<SaleJson> {
+        self.sales_page_by(&self.market.sale_ids_by_owner_id, account_id, cursor, limit)
+    }
+
+    // Cursor is the `nft_contract_id||token_id` of the last sale of the previous page
+    pub fn get_sales_by_nft_token_type_page(
+        &self,
+        token_type: String,
+        cursor: Option<String>,
+        limit: Option<u64>,
+    ) -> Page<SaleJson> {
+        self.sales_page_by(&self.market.sale_ids_by_token_type, token_type, cursor, limit)
+    }
+
+    // Cursor is the token_id of the last sale of the previous page
+    pub fn get_sales_by_nft_contract_id_page(
+        &self,
+        nft_contract_id: AccountId,
+        cursor: Option<TokenId>,
+        limit: Option<u64>,
+    ) -> Page<SaleJson> {
+        // `sale_ids` of the contract are ordered and start with the same prefix
+        let prefix = format!("{}{}", nft_contract_id, DELIMETER);
+        let from = match cursor {
+            Some(cursor) => Bound::Excluded(format!("{}{}", prefix, cursor)),
+            None => Bound::Included(prefix.clone()),
+        };
+        let (keys, next_cursor) = paginate(
+            self.market
+                .sale_ids
+                .range((from, Bound::Unbounded))
+                .map(|(key, _)| key)
+                .take_while(|key| key.starts_with(&prefix)),
+            limit,
+        );
+        self.sales_page(
+            keys.into_iter(),
+            next_cursor.map(|key| key[prefix.len()..].to_string()),
+        )
+    }
+
     pub fn get_supply_by_owner_id(&self, account_id: AccountId) -> U64 {
         let by_owner_id = self.market.by_owner_id.get(&account_id);
         if let Some(by_owner_id) = by_owner_id {
@@ -134,6 +284,39 @@ impl Market {
         tmp
     }
 
+    pub fn get_supply_by_allowed_buyer(&self, account_id: AccountId) -> U64 {
+        let by_allowed_buyer = self.market.by_allowed_buyer.get(&account_id);
+        if let Some(by_allowed_buyer) = by_allowed_buyer {
+            U64(by_allowed_buyer.len())
+        } else {
+            U64(0)
+        }
+    }
+
+    // Returns private sales reserved for the given buyer
+    pub fn get_sales_by_allowed_buyer(
+        &self,
+        account_id: AccountId,
+        from_index: U64,
+        limit: u64,
+    ) -> Vec<SaleJson> {
+        let mut tmp = vec![];
+        let by_allowed_buyer = self.market.by_allowed_buyer.get(&account_id);
+        let sales = if let Some(by_allowed_buyer) = by_allowed_buyer {
+            by_allowed_buyer
+        } else {
+            return vec![];
+        };
+        let keys = sales.as_vector();
+        let start = u64::from(from_index);
+        let end = min(start + limit, sales.len());
+        for i in start..end {
+            let sale = self.market.sales.get(&keys.get(i).unwrap()).unwrap();
+            tmp.push(self.json_from_sale(sale));
+        }
+        tmp
+    }
+
     pub fn get_sale(&self, nft_contract_id: AccountId, token_id: TokenId) -> Option<SaleJson> {
         let contract_and_token_id = format!("{}{}{}", &nft_contract_id, DELIMETER, token_id);
         self.market
@@ -155,6 +338,51 @@ impl Market {
             start: sale.start.map(|s| s.into()),
             end: sale.end.map(|e| e.into()),
             origins: sale.origins,
+            allowed_buyers: sale.allowed_buyers,
+            settle_at_end: sale.settle_at_end,
+        }
+    }
+}
+
+impl Market {
+    // Page of the sales indexed by `(key, contract_and_token_id)`
+    pub(crate) fn sales_page_by<K>(
+        &self,
+        index: &TreeMap<(K, ContractAndTokenId), ()>,
+        key: K,
+        cursor: Option<String>,
+        limit: Option<u64>,
+    ) -> Page<SaleJson>
+    where
+        K: Ord + Clone + BorshSerialize + BorshDeserialize,
+    {
+        let from = match cursor {
+            Some(cursor) => Bound::Excluded((key.clone(), cursor)),
+            None => Bound::Included((key.clone(), String::new())),
+        };
+        let (keys, next_cursor) = paginate(
+            index
+                .range((from, Bound::Unbounded))
+                .map(|(index_key, _)| index_key)
+                .take_while(|(index_key, _)| index_key == &key),
+            limit,
+        );
+        self.sales_page(
+            keys.into_iter().map(|(_, contract_and_token_id)| contract_and_token_id),
+            next_cursor.map(|(_, contract_and_token_id)| contract_and_token_id),
+        )
+    }
+
+    fn sales_page(
+        &self,
+        keys: impl Iterator<Item = ContractAndTokenId>,
+        next_cursor: Option<String>,
+    ) -> Page<SaleJson> {
+        Page {
+            items: keys
+                .map(|key| self.json_from_sale(self.market.sales.get(&key).unwrap()))
+                .collect(),
+            next_cursor,
         }
     }
 }
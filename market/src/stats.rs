@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+use crate::common::*;
+use crate::*;
+
+use crate::sale::{ContractAndTokenId, DELIMETER};
+
+// Sale conditions and token type of a listed sale
+pub(crate) type Listing<'a> = (&'a SaleConditions, Option<&'a String>);
+
+// Aggregates per NFT contract or token type
+// Auctions are counted per NFT contract only
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub struct CollectionStats {
+    pub listings: u64,
+    pub auctions: u64,
+    // The lowest sale price in each ft token
+    pub floor_prices: HashMap<FungibleTokenId, U128>,
+    // All-time volume of completed sales and auctions in each ft token
+    pub volume: HashMap<FungibleTokenId, U128>,
+}
+
+#[near_bindgen]
+impl Market {
+    pub fn get_collection_stats(&self, nft_contract_id: AccountId) -> CollectionStats {
+        self.market
+            .stats_by_nft_contract_id
+            .get(&nft_contract_id)
+            .unwrap_or_default()
+    }
+
+    pub fn get_token_type_stats(&self, token_type: String) -> CollectionStats {
+        self.market
+            .stats_by_nft_token_type
+            .get(&token_type)
+            .unwrap_or_default()
+    }
+}
+
+impl Market {
+    // Should be called after the sale and its indexes are updated
+    // `old` is the listing before the change, `new` is the listing after it
+    pub(crate) fn internal_update_sale_stats(
+        &mut self,
+        nft_contract_id: &AccountId,
+        token_id: &TokenId,
+        old: Option<Listing>,
+        new: Option<Listing>,
+    ) {
+        let mut stats = self
+            .market
+            .stats_by_nft_contract_id
+            .get(nft_contract_id)
+            .unwrap_or_default();
+        update_listing_stats(
+            &mut stats,
+            old.map(|(conditions, _)| conditions),
+            new.map(|(conditions, _)| conditions),
+            |ft_token_id| self.contract_floor_price(nft_contract_id, ft_token_id),
+        );
+        self.market
+            .stats_by_nft_contract_id
+            .insert(nft_contract_id, &stats);
+
+        let contract_and_token_id = format!("{}{}{}", nft_contract_id, DELIMETER, token_id);
+        let old_token_type = old.and_then(|(_, token_type)| token_type);
+        let new_token_type = new.and_then(|(_, token_type)| token_type);
+        if old_token_type == new_token_type {
+            if let Some(token_type) = new_token_type {
+                self.internal_update_token_type_stats(
+                    token_type,
+                    &contract_and_token_id,
+                    old.map(|(conditions, _)| conditions),
+                    new.map(|(conditions, _)| conditions),
+                );
+            }
+        } else {
+            if let Some(token_type) = old_token_type {
+                self.internal_update_token_type_stats(
+                    token_type,
+                    &contract_and_token_id,
+                    old.map(|(conditions, _)| conditions),
+                    None,
+                );
+            }
+            if let Some(token_type) = new_token_type {
+                self.internal_update_token_type_stats(
+                    token_type,
+                    &contract_and_token_id,
+                    None,
+                    new.map(|(conditions, _)| conditions),
+                );
+            }
+        }
+    }
+
+    pub(crate) fn internal_update_auction_stats(
+        &mut self,
+        nft_contract_id: &AccountId,
+        is_added: bool,
+    ) {
+        let mut stats = self
+            .market
+            .stats_by_nft_contract_id
+            .get(nft_contract_id)
+            .unwrap_or_default();
+        if is_added {
+            stats.auctions += 1;
+        } else {
+            stats.auctions = stats.auctions.saturating_sub(1);
+        }
+        self.market
+            .stats_by_nft_contract_id
+            .insert(nft_contract_id, &stats);
+    }
+
+    pub(crate) fn internal_add_volume(
+        &mut self,
+        nft_contract_id: &AccountId,
+        token_type: Option<&String>,
+        ft_token_id: &FungibleTokenId,
+        price: U128,
+    ) {
+        let mut stats = self
+            .market
+            .stats_by_nft_contract_id
+            .get(nft_contract_id)
+            .unwrap_or_default();
+        stats.volume.entry(ft_token_id.clone()).or_insert(U128(0)).0 += price.0;
+        self.market
+            .stats_by_nft_contract_id
+            .insert(nft_contract_id, &stats);
+        if let Some(token_type) = token_type {
+            let mut stats = self
+                .market
+                .stats_by_nft_token_type
+                .get(token_type)
+                .unwrap_or_default();
+            stats.volume.entry(ft_token_id.clone()).or_insert(U128(0)).0 += price.0;
+            self.market.stats_by_nft_token_type.insert(token_type, &stats);
+        }
+    }
+
+    fn internal_update_token_type_stats(
+        &mut self,
+        token_type: &String,
+        contract_and_token_id: &ContractAndTokenId,
+        old: Option<&SaleConditions>,
+        new: Option<&SaleConditions>,
+    ) {
+        self.internal_update_token_type_price_index(token_type, contract_and_token_id, old, new);
+        let mut stats = self
+            .market
+            .stats_by_nft_token_type
+            .get(token_type)
+            .unwrap_or_default();
+        update_listing_stats(&mut stats, old, new, |ft_token_id| {
+            self.token_type_floor_price(token_type, ft_token_id)
+        });
+        self.market.stats_by_nft_token_type.insert(token_type, &stats);
+    }
+
+    // Should be called after `sales_by_price` is updated
+    fn contract_floor_price(
+        &self,
+        nft_contract_id: &AccountId,
+        ft_token_id: &FungibleTokenId,
+    ) -> Option<U128> {
+        let index_id = format!("{}{}{}", nft_contract_id, DELIMETER, ft_token_id);
+        let by_price = self.market.sales_by_price.get(&index_id)?;
+        by_price.min().map(|(price, _)| U128(price))
+    }
+
+    // Should be called after `token_type_sales_by_price` is updated
+    fn token_type_floor_price(
+        &self,
+        token_type: &String,
+        ft_token_id: &FungibleTokenId,
+    ) -> Option<U128> {
+        let index_id = format!("{}{}{}", token_type, DELIMETER, ft_token_id);
+        let by_price = self.market.token_type_sales_by_price.get(&index_id)?;
+        by_price.min().map(|(price, _)| U128(price))
+    }
+}
+
+fn update_listing_stats(
+    stats: &mut CollectionStats,
+    old: Option<&SaleConditions>,
+    new: Option<&SaleConditions>,
+    floor_price: impl Fn(&FungibleTokenId) -> Option<U128>,
+) {
+    match (old.is_some(), new.is_some()) {
+        (false, true) => stats.listings += 1,
+        (true, false) => stats.listings = stats.listings.saturating_sub(1),
+        _ => {}
+    }
+    if let Some(old) = old {
+        for (ft_token_id, price) in old {
+            let is_floor = stats.floor_prices.get(ft_token_id).map(|floor| floor.0) == Some(price.0);
+            let is_unchanged = new
+                .and_then(|new| new.get(ft_token_id))
+                .map(|new_price| new_price.0)
+                == Some(price.0);
+            if is_floor && !is_unchanged {
+                match floor_price(ft_token_id) {
+                    Some(floor) => stats.floor_prices.insert(ft_token_id.clone(), floor),
+                    None => stats.floor_prices.remove(ft_token_id),
+                };
+            }
+        }
+    }
+    if let Some(new) = new {
+        for (ft_token_id, price) in new {
+            let floor = stats.floor_prices.entry(ft_token_id.clone()).or_insert(*price);
+            if price.0 < floor.0 {
+                *floor = *price;
+            }
+        }
+    }
+}
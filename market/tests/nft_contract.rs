@@ -129,6 +129,7 @@ fn nft_create_series_positive() {
             metadata: token_metadata,
             owner_id: user2.account_id(),
             royalty,
+            frozen: false,
         }
     )
 }
@@ -162,7 +163,7 @@ fn nft_mint_negative() {
     call!(root, nft.set_private_minting(true));
     let res = call!(
         user1,
-        nft.nft_mint(series_id.clone(), user1.account_id(), None),
+        nft.nft_mint(series_id.clone(), user1.account_id(), None, None),
         deposit = to_yocto("2")
     );
     if let ExecutionStatus::Failure(execution_error) =
@@ -179,7 +180,7 @@ fn nft_mint_negative() {
     // wrong series_id
     let res = call!(
         user1,
-        nft.nft_mint("200".to_string(), user1.account_id(), None),
+        nft.nft_mint("200".to_string(), user1.account_id(), None, None),
         deposit = to_yocto("2")
     );
     if let ExecutionStatus::Failure(execution_error) =
@@ -195,7 +196,7 @@ fn nft_mint_negative() {
     // only owner allowed to mint this series
     let res = call!(
         user2,
-        nft.nft_mint(series_id.clone(), user1.account_id(), None),
+        nft.nft_mint(series_id.clone(), user1.account_id(), None, None),
         deposit = to_yocto("2")
     );
     if let ExecutionStatus::Failure(execution_error) =
@@ -209,13 +210,13 @@ fn nft_mint_negative() {
     // Try to exceed max tokens
     call!(
         user1,
-        nft.nft_mint(series_id.clone(), user1.account_id(), None),
+        nft.nft_mint(series_id.clone(), user1.account_id(), None, None),
         deposit = to_yocto("2")
     )
     .assert_success();
     let res = call!(
         user1,
-        nft.nft_mint(series_id, user1.account_id(), None),
+        nft.nft_mint(series_id, user1.account_id(), None, None),
         deposit = to_yocto("2")
     );
     if let ExecutionStatus::Failure(execution_error) =
@@ -254,7 +255,7 @@ fn nft_mint_positive() {
     .unwrap_json();
     let token_id: String = call!(
         user1,
-        nft.nft_mint(series_id, user2.account_id(), None),
+        nft.nft_mint(series_id, user2.account_id(), None, None),
         deposit = to_yocto("2")
     )
     .unwrap_json();
@@ -273,3 +274,79 @@ fn nft_mint_positive() {
         }
     );
 }
+
+#[test]
+fn nft_transfer_call_events() {
+    let (root, _, nft) = init();
+    let user1 = root.create_user("user1".parse().unwrap(), to_yocto("1000"));
+    // `user2` has no contract, so `nft_on_transfer` fails and the transfer is reverted
+    let user2 = root.create_user("user2".parse().unwrap(), to_yocto("1000"));
+    let token_metadata = TokenMetadata {
+        title: Some("some title".to_string()),
+        description: None,
+        media: Some("ipfs://QmTqZsmhZLLbi8vxZwm21wjKRFRBUQFzMFtTiyh3DJ2CCz".to_string()),
+        media_hash: None,
+        copies: Some(1),
+        issued_at: None,
+        expires_at: None,
+        starts_at: None,
+        updated_at: None,
+        extra: None,
+        reference: None,
+        reference_hash: None,
+    };
+    let series_id: String = call!(
+        user1,
+        nft.nft_create_series(token_metadata, None),
+        deposit = to_yocto("0.005")
+    )
+    .unwrap_json();
+    let token_id: String = call!(
+        user1,
+        nft.nft_mint(series_id, user1.account_id(), None, None),
+        deposit = to_yocto("2")
+    )
+    .unwrap_json();
+
+    let outcome = call!(
+        user1,
+        nft.nft_transfer_call(
+            user2.account_id(),
+            token_id.clone(),
+            None,
+            Some("transfer memo".to_string()),
+            "".to_string()
+        ),
+        deposit = 1
+    );
+    // The forward transfer is logged by `nft_transfer_call`, the revert by `nft_resolve_transfer`
+    assert_eq!(
+        outcome.logs(),
+        &vec![format!(
+            r#"EVENT_JSON:{{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{{"old_owner_id":"user1","new_owner_id":"user2","token_ids":["{}"],"memo":"transfer memo"}}]}}"#,
+            token_id
+        )]
+    );
+    let events: Vec<String> = outcome
+        .promise_results()
+        .into_iter()
+        .flatten()
+        .flat_map(|result| result.logs().clone())
+        .filter(|log| log.starts_with("EVENT_JSON:"))
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            format!(
+                r#"EVENT_JSON:{{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{{"old_owner_id":"user1","new_owner_id":"user2","token_ids":["{}"],"memo":"transfer memo"}}]}}"#,
+                token_id
+            ),
+            format!(
+                r#"EVENT_JSON:{{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{{"old_owner_id":"user2","new_owner_id":"user1","token_ids":["{}"]}}]}}"#,
+                token_id
+            ),
+        ]
+    );
+    let token: Token = view!(nft.nft_token(token_id)).unwrap_json();
+    assert_eq!(token.owner_id, user1.account_id());
+}
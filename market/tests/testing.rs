@@ -43,7 +43,7 @@ fn test_fees_transfers() {
     for _ in 0..5 {
         call!(
             user1,
-            nft.nft_mint("1".to_string(), user1.account_id(), None),
+            nft.nft_mint("1".to_string(), user1.account_id(), None, None),
             deposit = to_yocto("0.01")
         )
         .assert_success();
@@ -88,7 +88,7 @@ fn test_fees_transfers() {
         + root.borrow_runtime().genesis.block_prod_time; // +1 block
     call!(
         user2,
-        market.auction_add_bid(1.into(), Some("near".to_string()), None),
+        market.auction_add_bid(1.into(), Some("near".to_string()), None, None),
         deposit = 10400
     )
     .assert_success();
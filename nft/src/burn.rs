@@ -0,0 +1,97 @@
+use crate::event::NftBurnData;
+use crate::*;
+use near_sdk::{assert_one_yocto, Promise};
+
+#[near_bindgen]
+impl Nft {
+    // Burns the token, can be called by the token owner or an approved account
+    // Storage freed by the token is refunded to the token owner
+    #[payable]
+    pub fn nft_burn(&mut self, token_id: TokenId, approval_id: Option<u64>, memo: Option<String>) {
+        assert_one_yocto();
+        let initial_storage_usage = env::storage_usage();
+        let owner_id = self
+            .tokens
+            .owner_by_id
+            .get(&token_id)
+            .unwrap_or_else(|| env::panic_str("Token not found"));
+        let sender_id = env::predecessor_account_id();
+        let authorized_id = if sender_id != owner_id {
+            let approved_account_ids = self
+                .tokens
+                .approvals_by_id
+                .as_ref()
+                .and_then(|by_id| by_id.get(&token_id))
+                .unwrap_or_default();
+            let actual_approval_id = approved_account_ids
+                .get(&sender_id)
+                .unwrap_or_else(|| env::panic_str("Sender not approved"));
+            require!(
+                approval_id.is_none() || approval_id == Some(*actual_approval_id),
+                format!(
+                    "The actual approval_id {} is different from the given approval_id {:?}",
+                    actual_approval_id, approval_id
+                )
+            );
+            Some(sender_id)
+        } else {
+            None
+        };
+
+        self.internal_burn(&token_id, &owner_id);
+
+        let freed_storage = initial_storage_usage.saturating_sub(env::storage_usage());
+        if freed_storage > 0 {
+            Promise::new(owner_id.clone())
+                .transfer(Balance::from(freed_storage) * env::storage_byte_cost());
+        }
+
+        NearEvent::nft_burn(vec![NftBurnData::new(
+            &owner_id,
+            vec![&token_id],
+            authorized_id.as_ref(),
+            memo.as_deref(),
+        )])
+        .emit();
+    }
+}
+
+impl Nft {
+    // Removes the token from the core, extensions and its series
+    pub(crate) fn internal_burn(&mut self, token_id: &TokenId, owner_id: &AccountId) {
+        self.tokens.owner_by_id.remove(token_id);
+        if let Some(token_metadata_by_id) = &mut self.tokens.token_metadata_by_id {
+            token_metadata_by_id.remove(token_id);
+        }
+        if let Some(tokens_per_owner) = &mut self.tokens.tokens_per_owner {
+            let mut token_ids = tokens_per_owner
+                .get(owner_id)
+                .unwrap_or_else(|| env::panic_str("Unable to access tokens per owner"));
+            token_ids.remove(token_id);
+            if token_ids.is_empty() {
+                tokens_per_owner.remove(owner_id);
+            } else {
+                tokens_per_owner.insert(owner_id, &token_ids);
+            }
+        }
+        if let Some(approvals_by_id) = &mut self.tokens.approvals_by_id {
+            approvals_by_id.remove(token_id);
+        }
+        if let Some(next_approval_id_by_id) = &mut self.tokens.next_approval_id_by_id {
+            next_approval_id_by_id.remove(token_id);
+        }
+
+        let token_series_id: TokenSeriesId = token_id
+            .split(TOKEN_DELIMETER)
+            .next()
+            .unwrap()
+            .to_string();
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .unwrap_or_else(|| env::panic_str("Token series does not exist"));
+        token_series.tokens.remove(token_id);
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
+    }
+}
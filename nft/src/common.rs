@@ -1,6 +1,6 @@
 pub use near_sdk::{
     borsh::{self, BorshDeserialize, BorshSerialize},
-    collections::{LazyOption, LookupSet, LookupMap, UnorderedMap, UnorderedSet},
+    collections::{LazyOption, LookupSet, LookupMap, TreeMap, UnorderedMap, UnorderedSet},
     env,
     json_types::{U128, U64},
     near_bindgen, require,
@@ -11,4 +11,12 @@ pub use near_sdk::{
 pub use near_contract_standards::non_fungible_token::{
     metadata::{NFTContractMetadata, TokenMetadata, NFT_METADATA_SPEC},
     refund_deposit, NonFungibleToken, Token, TokenId,
-};
\ No newline at end of file
+};
+
+// Page of a list view, `next_cursor` should be passed as `cursor` to get the next page
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
\ No newline at end of file
@@ -1,3 +1,4 @@
+use crate::token_series::TokenSeriesUpdate;
 use near_sdk::AccountId;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
@@ -8,6 +9,7 @@ use serde_with::skip_serializing_none;
 pub enum NearEvent<'a> {
     #[serde(borrow)]
     Nep171(Nep171Event<'a>),
+    NftSeries(NftSeriesEvent<'a>),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -114,6 +116,59 @@ impl<'a> NftBurnData<'a> {
     }
 }
 
+// Series events are not a part of NEP-171, so they have their own standard
+#[derive(Serialize, Debug)]
+pub struct NftSeriesEvent<'a> {
+    pub version: &'static str,
+    #[serde(flatten)]
+    pub event_kind: NftSeriesEventKind<'a>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+pub enum NftSeriesEventKind<'a> {
+    NftSeriesUpdate(Vec<NftSeriesUpdateData<'a>>),
+    NftSeriesOwnerUpdate(Vec<NftSeriesOwnerUpdateData<'a>>),
+}
+
+#[derive(Serialize, Debug)]
+pub struct NftSeriesUpdateData<'a> {
+    pub token_series_id: &'a str,
+    #[serde(flatten)]
+    pub update: &'a TokenSeriesUpdate,
+}
+
+impl<'a> NftSeriesUpdateData<'a> {
+    pub fn new(token_series_id: &'a str, update: &'a TokenSeriesUpdate) -> NftSeriesUpdateData<'a> {
+        Self { token_series_id, update }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct NftSeriesOwnerUpdateData<'a> {
+    pub token_series_id: &'a str,
+    pub old_owner_id: &'a str,
+    pub new_owner_id: &'a str,
+    pub move_royalty: bool,
+}
+
+impl<'a> NftSeriesOwnerUpdateData<'a> {
+    pub fn new(
+        token_series_id: &'a str,
+        old_owner_id: &'a AccountId,
+        new_owner_id: &'a AccountId,
+        move_royalty: bool,
+    ) -> NftSeriesOwnerUpdateData<'a> {
+        Self {
+            token_series_id,
+            old_owner_id: old_owner_id.as_str(),
+            new_owner_id: new_owner_id.as_str(),
+            move_royalty,
+        }
+    }
+}
+
 impl<'a> NearEvent<'a> {
     pub fn new_171(version: &'static str, event_kind: Nep171EventKind<'a>) -> Self {
         NearEvent::Nep171(Nep171Event { version, event_kind })
@@ -123,6 +178,20 @@ impl<'a> NearEvent<'a> {
         NearEvent::new_171("1.0.0", event_kind)
     }
 
+    pub fn new_nft_series_v1(event_kind: NftSeriesEventKind<'a>) -> Self {
+        NearEvent::NftSeries(NftSeriesEvent { version: "1.0.0", event_kind })
+    }
+
+    #[must_use = "don't forget to .emit() the event"]
+    pub fn nft_series_update(data: Vec<NftSeriesUpdateData<'a>>) -> Self {
+        NearEvent::new_nft_series_v1(NftSeriesEventKind::NftSeriesUpdate(data))
+    }
+
+    #[must_use = "don't forget to .emit() the event"]
+    pub fn nft_series_owner_update(data: Vec<NftSeriesOwnerUpdateData<'a>>) -> Self {
+        NearEvent::new_nft_series_v1(NftSeriesEventKind::NftSeriesOwnerUpdate(data))
+    }
+
     #[must_use = "don't forget to .emit() the event"]
     pub fn nft_burn(data: Vec<NftBurnData<'a>>) -> Self {
         NearEvent::new_171_v1(Nep171EventKind::NftBurn(data))
@@ -261,4 +330,35 @@ mod tests {
             r#"{"standard":"nep171","version":"1.0.0","event":"nft_transfer","data":[{"old_owner_id":"alice","new_owner_id":"bob","token_ids":["2","3"],"authorized_id":"bob","memo":"has memo"},{"old_owner_id":"bob","new_owner_id":"alice","token_ids":["0","1"]}]}"#
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn nft_series_update() {
+        let update = TokenSeriesUpdate {
+            description: Some("new description".to_string()),
+            copies: Some(5),
+            freeze: Some(true),
+            ..Default::default()
+        };
+        let log = NearEvent::nft_series_update(vec![NftSeriesUpdateData::new("1", &update)])
+            .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nft_series","version":"1.0.0","event":"nft_series_update","data":[{"token_series_id":"1","description":"new description","copies":5,"freeze":true}]}"#
+        );
+    }
+
+    #[test]
+    fn nft_series_owner_update() {
+        let log = NearEvent::nft_series_owner_update(vec![NftSeriesOwnerUpdateData::new(
+            "1",
+            &alice(),
+            &bob(),
+            true,
+        )])
+        .to_json_string();
+        assert_eq!(
+            log,
+            r#"{"standard":"nft_series","version":"1.0.0","event":"nft_series_owner_update","data":[{"token_series_id":"1","old_owner_id":"alice","new_owner_id":"bob","move_royalty":true}]}"#
+        );
+    }
+}
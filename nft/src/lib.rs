@@ -1,3 +1,7 @@
+mod burn;
+mod presale;
+mod public_mint;
+mod series_management;
 mod nft_core;
 mod token;
 
@@ -13,6 +17,8 @@ use event::NearEvent;
 use near_contract_standards::non_fungible_token::refund_deposit_to_account;
 use near_sdk::{ext_contract, Promise};
 use permissions::PrivateMint;
+use public_mint::SeriesMint;
+use token::assert_valid_token_metadata;
 use token_series::{TokenSeries, TokenSeriesId, TokenSeriesSale, TOKEN_DELIMETER};
 
 mod payouts;
@@ -20,8 +26,10 @@ use crate::{event::NftMintData, payouts::MAXIMUM_ROYALTY};
 
 use std::collections::HashMap;
 
-pub use token_series::TokenSeriesJson;
+pub use token_series::{PendingSeriesOwner, TokenSeriesJson, TokenSeriesUpdate};
 pub use payouts::Payout;
+pub use presale::{Presale, PresaleProof};
+pub use public_mint::MintConfig;
 
 // Since Near doesn't support multitoken(yet) by default we need to create some workaround
 // In this nft implementation every token is part of TokenSeries
@@ -34,6 +42,7 @@ pub struct Nft {
 
     token_series_by_id: UnorderedMap<TokenSeriesId, TokenSeries>,
     private_mint: PrivateMint,
+    mint_by_series_id: LookupMap<TokenSeriesId, SeriesMint>,
 }
 
 #[derive(BorshSerialize, BorshStorageKey)]
@@ -47,6 +56,11 @@ enum StorageKey {
     TokensBySeriesInner { token_series: String },
     TokensPerOwner { account_hash: Vec<u8> },
     Minters,
+    MintersBySeriesInner { token_series: String },
+    MintBySeriesId,
+    MintedByAccountInner { token_series: String },
+    MintAllowlistInner { token_series: String },
+    PresaleMintedInner { token_series: String },
 }
 
 #[near_bindgen]
@@ -91,20 +105,26 @@ impl Nft {
             metadata: LazyOption::new(StorageKey::Metadata, Some(&metadata)),
             token_series_by_id: UnorderedMap::new(b"s"),
             private_mint: PrivateMint::new(private_minting_enabled, minters),
+            mint_by_series_id: LookupMap::new(StorageKey::MintBySeriesId),
         }
     }
 
-    // public mint,
-    // mints NFT with metadata of token series
+    // public mint, can be called by the series owner or its minters,
+    // mints NFT with metadata of token series,
+    // `token_metadata` fields are merged over the series metadata (see `merge_metadata`)
     #[payable]
     pub fn nft_mint(
         &mut self,
         token_series_id: TokenSeriesId,
         receiver_id: AccountId,
         refund_id: Option<AccountId>,
+        token_metadata: Option<TokenMetadata>,
     ) -> TokenId {
         self.private_mint
             .panic_if_not_allowed(&env::predecessor_account_id());
+        if let Some(token_metadata) = token_metadata.as_ref() {
+            assert_valid_token_metadata(token_metadata);
+        }
         let refund_id = refund_id.unwrap_or_else(env::predecessor_account_id);
         let initial_storage_usage = env::storage_usage();
 
@@ -113,66 +133,96 @@ impl Nft {
             .get(&token_series_id)
             .expect("Token series does not exist");
         require!(
-            env::predecessor_account_id().eq(&token_series.owner_id),
+            token_series.is_minter(&env::predecessor_account_id()),
             "permission denied"
         );
         require!(
-            token_series.tokens.len() < token_series.metadata.copies.unwrap_or(u64::MAX),
+            token_series.minted < token_series.metadata.copies.unwrap_or(u64::MAX),
             "Max token minted"
         );
-        let token_id = format!(
-            "{}{}{}",
-            token_series_id,
-            TOKEN_DELIMETER,
-            token_series.tokens.len() + 1
+        let token_id = self.internal_mint_token(
+            &token_series_id,
+            &mut token_series,
+            &receiver_id,
+            token_metadata,
         );
-        let metadata = TokenMetadata {
-            title: None,       // ex. "Arch Nemesis: Mail Carrier" or "Parcel #5055"
-            description: None, // free-form description
-            media: None, // URL to associated media, preferably to decentralized, content-addressed storage
-            media_hash: None, // Base64-encoded sha256 hash of content referenced by the `media` field. Required if `media` is included.
-            copies: None, // number of copies of this set of metadata in existence when token was minted.
-            issued_at: Some(env::block_timestamp().to_string()), // ISO 8601 datetime when token was issued or minted
-            expires_at: None,     // ISO 8601 datetime when token expires
-            starts_at: None,      // ISO 8601 datetime when token starts being valid
-            updated_at: None,     // ISO 8601 datetime when token was last updated
-            extra: None, // anything extra the NFT wants to store on-chain. Can be stringified JSON.
-            reference: None, // URL to an off-chain JSON file with more info.
-            reference_hash: None, // Base64-encoded sha256 hash of JSON from reference field. Required if `reference` is included.
-        };
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
 
-        // implementation from NonFungibleToken::internal_mint_with_refund()
-        // Core behavior: every token must have an owner
-        self.tokens.owner_by_id.insert(&token_id, &receiver_id);
-        // Metadata extension: Save metadata, keep variable around to return later.
-        // Note that check above already panicked if metadata extension in use but no metadata
-        // provided to call.
-        self.tokens
-            .token_metadata_by_id
-            .as_mut()
-            .and_then(|by_id| by_id.insert(&token_id, &metadata));
+        refund_deposit_to_account(env::storage_usage() - initial_storage_usage, refund_id);
 
-        // Enumeration extension: Record tokens_per_owner for use with enumeration view methods.
-        if let Some(tokens_per_owner) = &mut self.tokens.tokens_per_owner {
-            let mut token_ids = tokens_per_owner.get(&receiver_id).unwrap_or_else(|| {
-                UnorderedSet::new(StorageKey::TokensPerOwner {
-                    account_hash: env::sha256(receiver_id.as_bytes()),
+        // Event
+        let mint_log = NftMintData::new(&receiver_id, vec![&token_id], None);
+        NearEvent::nft_mint(vec![mint_log]).emit();
+
+        token_id
+    }
+
+    // mints `count` tokens of the series to each receiver,
+    // the deposit for the whole batch is refunded once and a single event is emitted.
+    // Gas grows linearly with the number of tokens, see `nft_batch_mint_gas` test for the limits
+    #[payable]
+    pub fn nft_batch_mint(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        receivers: Vec<(AccountId, u64)>,
+        refund_id: Option<AccountId>,
+    ) -> Vec<TokenId> {
+        self.private_mint
+            .panic_if_not_allowed(&env::predecessor_account_id());
+        let refund_id = refund_id.unwrap_or_else(env::predecessor_account_id);
+        let initial_storage_usage = env::storage_usage();
+
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series does not exist");
+        require!(
+            token_series.is_minter(&env::predecessor_account_id()),
+            "permission denied"
+        );
+        let total = receivers
+            .iter()
+            .try_fold(0u64, |total, (_, count)| total.checked_add(*count))
+            .unwrap_or_else(|| env::panic_str("Max token minted"));
+        require!(total > 0, "Nothing to mint");
+        require!(
+            token_series.minted + total <= token_series.metadata.copies.unwrap_or(u64::MAX),
+            "Max token minted"
+        );
+
+        let mut token_ids_by_receiver = Vec::with_capacity(receivers.len());
+        for (receiver_id, count) in receivers.iter() {
+            let token_ids: Vec<TokenId> = (0..*count)
+                .map(|_| {
+                    self.internal_mint_token(&token_series_id, &mut token_series, receiver_id, None)
                 })
-            });
-            token_ids.insert(&token_id);
-            tokens_per_owner.insert(&receiver_id, &token_ids);
+                .collect();
+            token_ids_by_receiver.push((receiver_id, token_ids));
         }
-        token_series.tokens.insert(&token_id);
         self.token_series_by_id
             .insert(&token_series_id, &token_series);
 
         refund_deposit_to_account(env::storage_usage() - initial_storage_usage, refund_id);
 
         // Event
-        let mint_log = NftMintData::new(&receiver_id, vec![&token_id], None);
-        NearEvent::nft_mint(vec![mint_log]).emit();
+        let mint_logs = token_ids_by_receiver
+            .iter()
+            .filter(|(_, token_ids)| !token_ids.is_empty())
+            .map(|(receiver_id, token_ids)| {
+                NftMintData::new(
+                    receiver_id,
+                    token_ids.iter().map(String::as_str).collect(),
+                    None,
+                )
+            })
+            .collect();
+        NearEvent::nft_mint(mint_logs).emit();
 
-        token_id
+        token_ids_by_receiver
+            .into_iter()
+            .flat_map(|(_, token_ids)| token_ids)
+            .collect()
     }
 
     // Create series with given metadata and royalty
@@ -216,6 +266,16 @@ impl Nft {
                     .unwrap(),
                 ),
                 royalty: royalty_res,
+                minted: 0,
+                frozen: false,
+                pending_owner: None,
+                minters: TreeMap::new(
+                    StorageKey::MintersBySeriesInner {
+                        token_series: token_series_id.clone(),
+                    }
+                    .try_to_vec()
+                    .unwrap(),
+                ),
             },
         );
 
@@ -265,6 +325,70 @@ impl Nft {
     */
 }
 
+impl Nft {
+    // Mints the next edition of the series, the caller should check the limits and save the series
+    pub(crate) fn internal_mint_token(
+        &mut self,
+        token_series_id: &TokenSeriesId,
+        token_series: &mut TokenSeries,
+        receiver_id: &AccountId,
+        token_metadata: Option<TokenMetadata>,
+    ) -> TokenId {
+        // Editions are numbered by the minted count, so ids of burned tokens are not reused
+        token_series.minted += 1;
+        let token_id = format!(
+            "{}{}{}",
+            token_series_id,
+            TOKEN_DELIMETER,
+            token_series.minted
+        );
+        // only the per-token fields are stored, the rest comes from the series in `nft_token`
+        let metadata = token_metadata.map(|token_metadata| TokenMetadata {
+            copies: None,
+            issued_at: Some(env::block_timestamp().to_string()),
+            ..token_metadata
+        });
+        let metadata = metadata.unwrap_or(TokenMetadata {
+            title: None,       // ex. "Arch Nemesis: Mail Carrier" or "Parcel #5055"
+            description: None, // free-form description
+            media: None, // URL to associated media, preferably to decentralized, content-addressed storage
+            media_hash: None, // Base64-encoded sha256 hash of content referenced by the `media` field. Required if `media` is included.
+            copies: None, // number of copies of this set of metadata in existence when token was minted.
+            issued_at: Some(env::block_timestamp().to_string()), // ISO 8601 datetime when token was issued or minted
+            expires_at: None,     // ISO 8601 datetime when token expires
+            starts_at: None,      // ISO 8601 datetime when token starts being valid
+            updated_at: None,     // ISO 8601 datetime when token was last updated
+            extra: None, // anything extra the NFT wants to store on-chain. Can be stringified JSON.
+            reference: None, // URL to an off-chain JSON file with more info.
+            reference_hash: None, // Base64-encoded sha256 hash of JSON from reference field. Required if `reference` is included.
+        });
+
+        // implementation from NonFungibleToken::internal_mint_with_refund()
+        // Core behavior: every token must have an owner
+        self.tokens.owner_by_id.insert(&token_id, receiver_id);
+        // Metadata extension: Save metadata, keep variable around to return later.
+        // Note that check above already panicked if metadata extension in use but no metadata
+        // provided to call.
+        self.tokens
+            .token_metadata_by_id
+            .as_mut()
+            .and_then(|by_id| by_id.insert(&token_id, &metadata));
+
+        // Enumeration extension: Record tokens_per_owner for use with enumeration view methods.
+        if let Some(tokens_per_owner) = &mut self.tokens.tokens_per_owner {
+            let mut token_ids = tokens_per_owner.get(receiver_id).unwrap_or_else(|| {
+                UnorderedSet::new(StorageKey::TokensPerOwner {
+                    account_hash: env::sha256(receiver_id.as_bytes()),
+                })
+            });
+            token_ids.insert(&token_id);
+            tokens_per_owner.insert(receiver_id, &token_ids);
+        }
+        token_series.tokens.insert(&token_id);
+        token_id
+    }
+}
+
 near_contract_standards::impl_non_fungible_token_approval!(Nft, tokens);
 near_contract_standards::impl_non_fungible_token_enumeration!(Nft, tokens);
 
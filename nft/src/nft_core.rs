@@ -1,6 +1,10 @@
 use crate::*;
 use crate::event::NftTransferData;
-use near_contract_standards::non_fungible_token::{core::NonFungibleTokenCore, Token};
+use crate::token::merge_metadata;
+use near_contract_standards::non_fungible_token::core::{
+    NonFungibleTokenCore, NonFungibleTokenResolver,
+};
+use near_contract_standards::non_fungible_token::Token;
 
 #[near_bindgen]
 impl NonFungibleTokenCore for Nft {
@@ -37,8 +41,28 @@ impl NonFungibleTokenCore for Nft {
         memo: Option<String>,
         msg: String,
     ) -> near_sdk::PromiseOrValue<bool> {
-        self.tokens
-            .nft_transfer_call(receiver_id, token_id, approval_id, memo, msg)
+        let old_owner_id =
+            self.tokens.owner_by_id.get(&token_id).unwrap_or_else(|| env::panic_str("Token not found"));
+        let authorized_id = if old_owner_id != env::predecessor_account_id() {
+            Some(env::predecessor_account_id())
+        } else {
+            None
+        };
+        let result = self.tokens.nft_transfer_call(
+            receiver_id.clone(),
+            token_id.clone(),
+            approval_id,
+            memo.clone(),
+            msg,
+        );
+        NearEvent::nft_transfer(vec![NftTransferData::new(
+            &old_owner_id,
+            &receiver_id,
+            vec![&token_id],
+            authorized_id.as_ref(),
+            memo.as_deref(),
+        )]).emit();
+        result
     }
 
     fn nft_token(
@@ -48,7 +72,7 @@ impl NonFungibleTokenCore for Nft {
         let owner_id = self.tokens.owner_by_id.get(&token_id)?;
         let mut token_id_iter = token_id.split(TOKEN_DELIMETER);
         let token_series_id = token_id_iter.next().unwrap().parse().unwrap();
-        let mut series_metadata = self
+        let series_metadata = self
             .token_series_by_id
             .get(&token_series_id)
             .unwrap()
@@ -65,13 +89,41 @@ impl NonFungibleTokenCore for Nft {
             .approvals_by_id
             .as_ref()
             .and_then(|by_id| by_id.get(&token_id).or_else(|| Some(HashMap::new())));
-        series_metadata.issued_at = token_metadata.issued_at;
-        series_metadata.copies = None;
         Some(Token {
             token_id,
             owner_id,
-            metadata: Some(series_metadata),
+            metadata: Some(merge_metadata(series_metadata, token_metadata)),
             approved_account_ids,
         })
     }
 }
+
+#[near_bindgen]
+impl NonFungibleTokenResolver for Nft {
+    // Returns the token to the previous owner if `nft_on_transfer` asked for it or failed
+    #[private]
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: near_contract_standards::non_fungible_token::TokenId,
+        approved_account_ids: Option<HashMap<AccountId, u64>>,
+    ) -> bool {
+        let transferred = self.tokens.nft_resolve_transfer(
+            previous_owner_id.clone(),
+            receiver_id.clone(),
+            token_id.clone(),
+            approved_account_ids,
+        );
+        if !transferred {
+            NearEvent::nft_transfer(vec![NftTransferData::new(
+                &receiver_id,
+                &previous_owner_id,
+                vec![&token_id],
+                None,
+                None,
+            )]).emit();
+        }
+        transferred
+    }
+}
@@ -0,0 +1,53 @@
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+
+// Presale phase of the public minting, only accounts from the Merkle tree of
+// (account_id, allowance) can mint up to their allowance.
+// Leaves are sha256 of 0x00 followed by borsh serialized (account_id, allowance),
+// nodes are sha256 of 0x01 followed by the sorted concatenated children,
+// so proofs don't contain positions and a node can't be passed off as a leaf
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub struct Presale {
+    pub merkle_root: Base64VecU8,
+    // Presale lasts from the config `start` until `end`, then the public phase starts
+    pub end: U64,
+    // Price during the presale, the config `price` if not set
+    pub price: Option<U128>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PresaleProof {
+    pub allowance: u64,
+    // Sibling hashes from the leaf up to the root
+    pub proof: Vec<Base64VecU8>,
+}
+
+impl Presale {
+    pub(crate) fn assert_valid(&self) {
+        require!(self.merkle_root.0.len() == 32, "Merkle root has to be 32 bytes");
+    }
+
+    pub(crate) fn verify(&self, account_id: &AccountId, proof: &PresaleProof) -> bool {
+        let data = (account_id, proof.allowance).try_to_vec().unwrap();
+        let leaf = env::sha256(&[&[LEAF_PREFIX], data.as_slice()].concat());
+        let root = proof
+            .proof
+            .iter()
+            .fold(leaf, |hash, sibling| hash_pair(&hash, &sibling.0));
+        root == self.merkle_root.0
+    }
+}
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+fn hash_pair(a: &[u8], b: &[u8]) -> Vec<u8> {
+    if a <= b {
+        env::sha256(&[&[NODE_PREFIX], a, b].concat())
+    } else {
+        env::sha256(&[&[NODE_PREFIX], b, a].concat())
+    }
+}
@@ -0,0 +1,303 @@
+use crate::event::NftMintData;
+use crate::payouts::{royalty_to_payout, ROYALTY_TOTAL_VALUE};
+use crate::presale::{Presale, PresaleProof};
+use crate::*;
+use near_sdk::Promise;
+
+// Public sale of the series tokens, anyone can mint paying the `price` in NEAR.
+// Timestamps are in nanoseconds
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub struct MintConfig {
+    pub price: U128,
+    pub start: Option<U64>,
+    pub end: Option<U64>,
+    pub limit_per_account: Option<u64>,
+    // If enabled, only accounts from the allowlist can mint, up to their allowance
+    pub allowlist_enabled: bool,
+    // Shares of the proceeds in basis points, should sum up to `ROYALTY_TOTAL_VALUE`.
+    // If not set, proceeds are split according to the series royalty and the rest goes to the owner
+    pub primary_split: Option<HashMap<AccountId, u32>>,
+    pub presale: Option<Presale>,
+}
+
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct SeriesMint {
+    // `None` if the public minting is disabled
+    pub config: Option<MintConfig>,
+    pub minted_by_account: LookupMap<AccountId, u64>,
+    pub allowlist: LookupMap<AccountId, u64>,
+    // Tokens minted by the account during the presale
+    pub presale_minted: LookupMap<AccountId, u64>,
+}
+
+#[near_bindgen]
+impl Nft {
+    // Enables the public minting of the series or disables it if `config` is `None`,
+    // can be called only by the series owner
+    #[payable]
+    pub fn nft_set_mint_config(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        config: Option<MintConfig>,
+    ) {
+        let initial_storage_usage = env::storage_usage();
+        let token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series does not exist");
+        require!(
+            env::predecessor_account_id().eq(&token_series.owner_id),
+            "permission denied"
+        );
+        if let Some(config) = config.as_ref() {
+            if let (Some(start), Some(end)) = (config.start, config.end) {
+                require!(start.0 < end.0, "start should be before end");
+            }
+            if let Some(presale) = config.presale.as_ref() {
+                presale.assert_valid();
+                if let Some(end) = config.end {
+                    require!(presale.end.0 <= end.0, "presale should end before end");
+                }
+            }
+            if let Some(primary_split) = config.primary_split.as_ref() {
+                require!(
+                    primary_split.values().map(|v| *v as u128).sum::<u128>() == ROYALTY_TOTAL_VALUE,
+                    format!("primary split should sum up to {}", ROYALTY_TOTAL_VALUE)
+                );
+            }
+        }
+        let mut series_mint = self.internal_series_mint(&token_series_id);
+        series_mint.config = config;
+        self.mint_by_series_id
+            .insert(&token_series_id, &series_mint);
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage_usage));
+    }
+
+    // Sets the number of tokens each account can mint when the allowlist is enabled,
+    // zero allowance removes the account from the allowlist
+    #[payable]
+    pub fn nft_set_mint_allowlist(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        allowances: Vec<(AccountId, u64)>,
+    ) {
+        let initial_storage_usage = env::storage_usage();
+        let token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series does not exist");
+        require!(
+            env::predecessor_account_id().eq(&token_series.owner_id),
+            "permission denied"
+        );
+        let mut series_mint = self.internal_series_mint(&token_series_id);
+        for (account_id, allowance) in allowances {
+            if allowance == 0 {
+                series_mint.allowlist.remove(&account_id);
+            } else {
+                series_mint.allowlist.insert(&account_id, &allowance);
+            }
+        }
+        self.mint_by_series_id
+            .insert(&token_series_id, &series_mint);
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage_usage));
+    }
+
+    // Mints a token of the series to the receiver (the caller by default).
+    // Attached deposit should cover the price and the storage, the rest is refunded.
+    // During the presale the caller should provide its allowance with the Merkle proof
+    #[payable]
+    pub fn nft_public_mint(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        receiver_id: Option<AccountId>,
+        presale_proof: Option<PresaleProof>,
+    ) -> TokenId {
+        let initial_storage_usage = env::storage_usage();
+        let buyer_id = env::predecessor_account_id();
+        let receiver_id = receiver_id.unwrap_or_else(|| buyer_id.clone());
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series does not exist");
+        let mut series_mint = self
+            .mint_by_series_id
+            .get(&token_series_id)
+            .unwrap_or_else(|| env::panic_str("Public minting is not enabled"));
+        let config = series_mint
+            .config
+            .clone()
+            .unwrap_or_else(|| env::panic_str("Public minting is not enabled"));
+        let now = env::block_timestamp();
+        if let Some(start) = config.start {
+            require!(now >= start.0, "Minting hasn't started yet");
+        }
+        if let Some(end) = config.end {
+            require!(now < end.0, "Minting has ended");
+        }
+        require!(
+            token_series.minted < token_series.metadata.copies.unwrap_or(u64::MAX),
+            "Max token minted"
+        );
+        let minted_by_buyer = series_mint.minted_by_account.get(&buyer_id).unwrap_or_default() + 1;
+        if let Some(limit_per_account) = config.limit_per_account {
+            require!(minted_by_buyer <= limit_per_account, "Mint limit per account exceeded");
+        }
+        let presale = config.presale.as_ref().filter(|presale| now < presale.end.0);
+        let price = if let Some(presale) = presale {
+            let presale_proof =
+                presale_proof.unwrap_or_else(|| env::panic_str("Presale requires a proof"));
+            require!(
+                presale.verify(&buyer_id, &presale_proof),
+                "Invalid presale proof"
+            );
+            let presale_minted = series_mint.presale_minted.get(&buyer_id).unwrap_or_default() + 1;
+            require!(
+                presale_minted <= presale_proof.allowance,
+                "Presale allowance exceeded"
+            );
+            series_mint
+                .presale_minted
+                .insert(&buyer_id, &presale_minted);
+            presale.price.unwrap_or(config.price)
+        } else {
+            if config.allowlist_enabled {
+                let allowance = series_mint
+                    .allowlist
+                    .get(&buyer_id)
+                    .unwrap_or_else(|| env::panic_str("Account is not in the allowlist"));
+                require!(minted_by_buyer <= allowance, "Allowance exceeded");
+            }
+            config.price
+        };
+
+        let token_id =
+            self.internal_mint_token(&token_series_id, &mut token_series, &receiver_id, None);
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
+        series_mint
+            .minted_by_account
+            .insert(&buyer_id, &minted_by_buyer);
+        self.mint_by_series_id
+            .insert(&token_series_id, &series_mint);
+
+        let storage_cost = Balance::from(env::storage_usage() - initial_storage_usage)
+            * env::storage_byte_cost();
+        let required = price.0 + storage_cost;
+        let attached = env::attached_deposit();
+        require!(
+            attached >= required,
+            format!("Must attach {} yoctoNEAR to cover the price and storage", required)
+        );
+        let refund = attached - required;
+        if refund > 1 {
+            Promise::new(buyer_id).transfer(refund);
+        }
+        pay_proceeds(&token_series, config.primary_split.as_ref(), price.0);
+
+        let mint_log = NftMintData::new(&receiver_id, vec![&token_id], None);
+        NearEvent::nft_mint(vec![mint_log]).emit();
+
+        token_id
+    }
+
+    // Number of tokens the account has minted during the presale
+    pub fn nft_presale_minted(&self, token_series_id: TokenSeriesId, account_id: AccountId) -> U64 {
+        let minted = self
+            .mint_by_series_id
+            .get(&token_series_id)
+            .and_then(|series_mint| series_mint.presale_minted.get(&account_id))
+            .unwrap_or_default();
+        U64(minted)
+    }
+
+    pub fn nft_mint_config(&self, token_series_id: TokenSeriesId) -> Option<MintConfig> {
+        self.mint_by_series_id
+            .get(&token_series_id)
+            .and_then(|series_mint| series_mint.config)
+    }
+
+    // Number of tokens that can still be minted, `None` if the series is unlimited
+    pub fn nft_series_remaining_supply(&self, token_series_id: TokenSeriesId) -> Option<U64> {
+        let token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .unwrap_or_else(|| env::panic_str("Could not find token series"));
+        token_series
+            .metadata
+            .copies
+            .map(|copies| U64(copies.saturating_sub(token_series.minted)))
+    }
+
+    // Number of tokens the account can still mint with `nft_public_mint`,
+    // `None` if it's limited only by the series supply
+    pub fn nft_mint_allowance(
+        &self,
+        token_series_id: TokenSeriesId,
+        account_id: AccountId,
+    ) -> Option<U64> {
+        let series_mint = self.mint_by_series_id.get(&token_series_id)?;
+        let config = series_mint.config?;
+        let minted = series_mint.minted_by_account.get(&account_id).unwrap_or_default();
+        let allowance = if config.allowlist_enabled {
+            let allowance = series_mint.allowlist.get(&account_id).unwrap_or_default();
+            Some(config.limit_per_account.map_or(allowance, |limit| limit.min(allowance)))
+        } else {
+            config.limit_per_account
+        };
+        allowance.map(|allowance| U64(allowance.saturating_sub(minted)))
+    }
+}
+
+impl Nft {
+    fn internal_series_mint(&self, token_series_id: &TokenSeriesId) -> SeriesMint {
+        self.mint_by_series_id
+            .get(token_series_id)
+            .unwrap_or_else(|| SeriesMint {
+                config: None,
+                minted_by_account: LookupMap::new(
+                    StorageKey::MintedByAccountInner {
+                        token_series: token_series_id.clone(),
+                    }
+                    .try_to_vec()
+                    .unwrap(),
+                ),
+                allowlist: LookupMap::new(
+                    StorageKey::MintAllowlistInner {
+                        token_series: token_series_id.clone(),
+                    }
+                    .try_to_vec()
+                    .unwrap(),
+                ),
+                presale_minted: LookupMap::new(
+                    StorageKey::PresaleMintedInner {
+                        token_series: token_series_id.clone(),
+                    }
+                    .try_to_vec()
+                    .unwrap(),
+                ),
+            })
+    }
+}
+
+// Splits the proceeds by `primary_split` or the series royalty, the rest goes to the series owner
+fn pay_proceeds(
+    token_series: &TokenSeries,
+    primary_split: Option<&HashMap<AccountId, u32>>,
+    price: Balance,
+) {
+    let shares = primary_split.unwrap_or(&token_series.royalty);
+    let mut paid = 0;
+    for (account_id, share) in shares.iter() {
+        let amount = royalty_to_payout(*share, price).0;
+        if amount > 0 && account_id != &token_series.owner_id {
+            Promise::new(account_id.clone()).transfer(amount);
+            paid += amount;
+        }
+    }
+    if price > paid {
+        Promise::new(token_series.owner_id.clone()).transfer(price - paid);
+    }
+}
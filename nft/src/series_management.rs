@@ -0,0 +1,199 @@
+use crate::event::{NftSeriesOwnerUpdateData, NftSeriesUpdateData};
+use crate::token::{assert_valid_media, assert_valid_reference};
+use crate::token_series::{PendingSeriesOwner, TokenSeriesUpdate, MAX_SERIES_MINTERS};
+use crate::*;
+use near_sdk::assert_one_yocto;
+
+#[near_bindgen]
+impl Nft {
+    // Updates the series metadata and royalty, can be called only by the series owner.
+    // `copies` can't be less than the number of minted tokens,
+    // a frozen series can't be updated anymore
+    #[payable]
+    pub fn nft_update_series(&mut self, token_series_id: TokenSeriesId, update: TokenSeriesUpdate) {
+        let initial_storage_usage = env::storage_usage();
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series does not exist");
+        require!(
+            env::predecessor_account_id().eq(&token_series.owner_id),
+            "permission denied"
+        );
+        require!(!token_series.frozen, "Series is frozen");
+
+        let metadata = &mut token_series.metadata;
+        if let Some(description) = update.description.as_ref() {
+            metadata.description = Some(description.clone());
+        }
+        // Only the updated fields are validated, series stored before the validation can be updated
+        if update.media.is_some() || update.media_hash.is_some() {
+            assert_valid_media(&update.media, &update.media_hash);
+            metadata.media = update.media.clone();
+            metadata.media_hash = update.media_hash.clone();
+        }
+        if update.reference.is_some() || update.reference_hash.is_some() {
+            assert_valid_reference(&update.reference, &update.reference_hash);
+            metadata.reference = update.reference.clone();
+            metadata.reference_hash = update.reference_hash.clone();
+        }
+        if let Some(copies) = update.copies {
+            require!(
+                copies >= token_series.minted,
+                "copies can't be less than the number of minted tokens"
+            );
+            metadata.copies = Some(copies);
+        }
+        metadata.updated_at = Some(env::block_timestamp().to_string());
+        if let Some(royalty) = update.royalty.as_ref() {
+            let total_payouts: u32 = royalty.values().sum();
+            require!(
+                total_payouts <= MAXIMUM_ROYALTY,
+                format!("maximum royalty cap exceeded {}", MAXIMUM_ROYALTY)
+            );
+            token_series.royalty = royalty.clone();
+        }
+        if update.freeze == Some(true) {
+            token_series.frozen = true;
+        }
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
+
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage_usage));
+
+        NearEvent::nft_series_update(vec![NftSeriesUpdateData::new(&token_series_id, &update)])
+            .emit();
+    }
+
+    // First step of the series ownership transfer, can be called only by the series owner.
+    // `new_owner_id` should accept the ownership with `nft_series_accept_ownership`,
+    // `None` cancels the pending transfer.
+    // If `move_royalty` is set, the royalty of the current owner is moved to the new owner,
+    // otherwise it stays with the current owner.
+    // Minters of the series are removed unless `keep_minters` is set.
+    // Requires 1 yoctoNEAR, the pending owner is stored at the expense of the contract
+    #[payable]
+    pub fn nft_series_transfer_ownership(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        new_owner_id: Option<AccountId>,
+        move_royalty: Option<bool>,
+        keep_minters: Option<bool>,
+    ) {
+        assert_one_yocto();
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series does not exist");
+        require!(
+            env::predecessor_account_id().eq(&token_series.owner_id),
+            "permission denied"
+        );
+        if let Some(new_owner_id) = new_owner_id.as_ref() {
+            require!(
+                new_owner_id != &token_series.owner_id,
+                "The account already owns the series"
+            );
+        }
+        token_series.pending_owner = new_owner_id.map(|owner_id| PendingSeriesOwner {
+            owner_id,
+            move_royalty: move_royalty.unwrap_or_default(),
+            keep_minters: keep_minters.unwrap_or_default(),
+        });
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
+    }
+
+    // Second step of the series ownership transfer, can be called only by the pending owner.
+    // Requires 1 yoctoNEAR
+    #[payable]
+    pub fn nft_series_accept_ownership(&mut self, token_series_id: TokenSeriesId) {
+        assert_one_yocto();
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series does not exist");
+        let pending_owner = token_series
+            .pending_owner
+            .take()
+            .unwrap_or_else(|| env::panic_str("No pending ownership transfer"));
+        require!(
+            env::predecessor_account_id() == pending_owner.owner_id,
+            "permission denied"
+        );
+        let old_owner_id = std::mem::replace(
+            &mut token_series.owner_id,
+            pending_owner.owner_id.clone(),
+        );
+        if pending_owner.move_royalty {
+            if let Some(royalty) = token_series.royalty.remove(&old_owner_id) {
+                *token_series
+                    .royalty
+                    .entry(pending_owner.owner_id.clone())
+                    .or_default() += royalty;
+            }
+        }
+        if !pending_owner.keep_minters {
+            token_series.minters.clear();
+        }
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
+
+        NearEvent::nft_series_owner_update(vec![NftSeriesOwnerUpdateData::new(
+            &token_series_id,
+            &old_owner_id,
+            &pending_owner.owner_id,
+            pending_owner.move_royalty,
+        )])
+        .emit();
+    }
+
+    // Allows the account to mint the series, can be called only by the series owner.
+    // A series can have up to `MAX_SERIES_MINTERS` minters.
+    // Returns `true` if the account wasn't a minter before
+    #[payable]
+    pub fn nft_series_grant_minter(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        account_id: AccountId,
+    ) -> bool {
+        let initial_storage_usage = env::storage_usage();
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series does not exist");
+        require!(
+            env::predecessor_account_id().eq(&token_series.owner_id),
+            "permission denied"
+        );
+        let is_added = token_series.minters.insert(&account_id, &()).is_none();
+        require!(
+            token_series.minters.len() <= MAX_SERIES_MINTERS,
+            "Too many minters of the series"
+        );
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
+        refund_deposit(env::storage_usage().saturating_sub(initial_storage_usage));
+        is_added
+    }
+
+    // Returns `true` if the account was a minter of the series
+    pub fn nft_series_deny_minter(
+        &mut self,
+        token_series_id: TokenSeriesId,
+        account_id: AccountId,
+    ) -> bool {
+        let mut token_series = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .expect("Token series does not exist");
+        require!(
+            env::predecessor_account_id().eq(&token_series.owner_id),
+            "permission denied"
+        );
+        let is_removed = token_series.minters.remove(&account_id).is_some();
+        self.token_series_by_id
+            .insert(&token_series_id, &token_series);
+        is_removed
+    }
+}
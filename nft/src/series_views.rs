@@ -1,4 +1,4 @@
-use crate::token_series::TokenSeriesJson;
+use crate::token_series::{PendingSeriesOwner, TokenSeriesJson};
 use crate::*;
 
 #[near_bindgen]
@@ -9,6 +9,7 @@ impl Nft {
             metadata: token_series.metadata,
             owner_id: token_series.owner_id,
             royalty: token_series.royalty,
+            frozen: token_series.frozen,
         }
     }
 
@@ -30,10 +31,49 @@ impl Nft {
                 metadata: token_series.metadata,
                 owner_id: token_series.owner_id,
                 royalty: token_series.royalty,
+                frozen: token_series.frozen,
             })
             .collect()
     }
 
+    // Series are never removed and their ids are sequential,
+    // so the cursor is the id of the last series of the previous page
+    pub fn nft_series_page(
+        &self,
+        cursor: Option<TokenSeriesId>,
+        limit: Option<u64>,
+    ) -> Page<TokenSeriesJson> {
+        let from: u64 = cursor
+            .map(|cursor| cursor.parse().expect("Invalid cursor"))
+            .unwrap_or_default();
+        let total = self.token_series_by_id.len();
+        let limit = limit.unwrap_or(u64::MAX);
+        require!(limit != 0, "Cannot provide limit of 0.");
+        let to = std::cmp::min(total, from.saturating_add(limit));
+        let items = (from + 1..=to)
+            .map(|token_series_id| {
+                let token_series = self
+                    .token_series_by_id
+                    .get(&token_series_id.to_string())
+                    .unwrap();
+                TokenSeriesJson {
+                    metadata: token_series.metadata,
+                    owner_id: token_series.owner_id,
+                    royalty: token_series.royalty,
+                    frozen: token_series.frozen,
+                }
+            })
+            .collect();
+        Page {
+            items,
+            next_cursor: if to < total {
+                Some(to.to_string())
+            } else {
+                None
+            },
+        }
+    }
+
     pub fn nft_supply_for_series(&self, token_series_id: TokenSeriesId) -> U128 {
         U128::from(
             self.token_series_by_id
@@ -43,4 +83,80 @@ impl Nft {
                 .len() as u128,
         )
     }
+
+    pub fn nft_series_pending_owner(
+        &self,
+        token_series_id: TokenSeriesId,
+    ) -> Option<PendingSeriesOwner> {
+        self.token_series_by_id
+            .get(&token_series_id)
+            .unwrap_or_else(|| env::panic_str("Could not find token series"))
+            .pending_owner
+    }
+
+    // Accounts allowed to mint the series besides its owner
+    pub fn nft_series_minters(
+        &self,
+        token_series_id: TokenSeriesId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<AccountId> {
+        let start_index: u128 = from_index.map(From::from).unwrap_or_default();
+        let limit = limit.map(|v| v as usize).unwrap_or(usize::MAX);
+        self.token_series_by_id
+            .get(&token_series_id)
+            .unwrap_or_else(|| env::panic_str("Could not find token series"))
+            .minters
+            .iter()
+            .skip(start_index as usize)
+            .take(limit)
+            .map(|(account_id, _)| account_id)
+            .collect()
+    }
+
+    // Cursor is the last minter of the previous page
+    pub fn nft_series_minters_page(
+        &self,
+        token_series_id: TokenSeriesId,
+        cursor: Option<AccountId>,
+        limit: Option<u64>,
+    ) -> Page<AccountId> {
+        let limit = limit.unwrap_or(u64::MAX);
+        require!(limit != 0, "Cannot provide limit of 0.");
+        let minters = self
+            .token_series_by_id
+            .get(&token_series_id)
+            .unwrap_or_else(|| env::panic_str("Could not find token series"))
+            .minters;
+        let mut items: Vec<AccountId> = match cursor {
+            Some(cursor) => minters
+                .iter_from(cursor)
+                .take(limit.saturating_add(1) as usize)
+                .map(|(account_id, _)| account_id)
+                .collect(),
+            None => minters
+                .iter()
+                .take(limit.saturating_add(1) as usize)
+                .map(|(account_id, _)| account_id)
+                .collect(),
+        };
+        let next_cursor = if items.len() as u64 > limit {
+            items.pop();
+            items.last().map(|account_id| account_id.to_string())
+        } else {
+            None
+        };
+        Page { items, next_cursor }
+    }
+
+    pub fn nft_series_is_minter(
+        &self,
+        token_series_id: TokenSeriesId,
+        account_id: AccountId,
+    ) -> bool {
+        self.token_series_by_id
+            .get(&token_series_id)
+            .unwrap_or_else(|| env::panic_str("Could not find token series"))
+            .is_minter(&account_id)
+    }
 }
@@ -1,7 +1,8 @@
 
 use near_contract_standards::non_fungible_token::metadata::TokenMetadata;
+use near_sdk::json_types::Base64VecU8;
 use near_sdk::serde::{Deserialize, Serialize};
-use near_sdk::AccountId;
+use near_sdk::{require, AccountId};
 use std::collections::HashMap;
 
 /// Note that token IDs for NFTs are strings on NEAR. It's still fine to use autoincrementing numbers as unique IDs if desired, but they should be stringified. This is to make IDs more future-proof as chain-agnostic conventions and standards arise, and allows for more flexibility with considerations like bridging NFTs across chains, etc.
@@ -15,4 +16,58 @@ pub struct Token {
     pub owner_id: AccountId,
     pub metadata: Option<TokenMetadata>,
     pub approved_account_ids: Option<HashMap<AccountId, u64>>,
-}
\ No newline at end of file
+}
+/// Per-token metadata set on mint is merged over the series metadata:
+/// a field set for the token takes precedence over the series one,
+/// `media`/`media_hash` and `reference`/`reference_hash` are taken as pairs,
+/// so a token media is never shown with the series hash.
+/// `issued_at` is always the mint time and `copies` is never set for a token.
+pub(crate) fn merge_metadata(series: TokenMetadata, token: TokenMetadata) -> TokenMetadata {
+    let (media, media_hash) = if token.media.is_some() {
+        (token.media, token.media_hash)
+    } else {
+        (series.media, series.media_hash)
+    };
+    let (reference, reference_hash) = if token.reference.is_some() {
+        (token.reference, token.reference_hash)
+    } else {
+        (series.reference, series.reference_hash)
+    };
+    TokenMetadata {
+        title: token.title.or(series.title),
+        description: token.description.or(series.description),
+        media,
+        media_hash,
+        copies: None,
+        issued_at: token.issued_at,
+        expires_at: token.expires_at.or(series.expires_at),
+        starts_at: token.starts_at.or(series.starts_at),
+        updated_at: token.updated_at.or(series.updated_at),
+        extra: token.extra.or(series.extra),
+        reference,
+        reference_hash,
+    }
+}
+
+/// Hashes are optional, but if set they should be sha256 of the content they refer to
+pub(crate) fn assert_valid_token_metadata(metadata: &TokenMetadata) {
+    assert_valid_media(&metadata.media, &metadata.media_hash);
+    assert_valid_reference(&metadata.reference, &metadata.reference_hash);
+}
+
+pub(crate) fn assert_valid_media(media: &Option<String>, media_hash: &Option<Base64VecU8>) {
+    if let Some(media_hash) = media_hash {
+        require!(media.is_some(), "Media hash requires media");
+        require!(media_hash.0.len() == 32, "Media hash has to be 32 bytes");
+    }
+}
+
+pub(crate) fn assert_valid_reference(
+    reference: &Option<String>,
+    reference_hash: &Option<Base64VecU8>,
+) {
+    if let Some(reference_hash) = reference_hash {
+        require!(reference.is_some(), "Reference hash requires reference");
+        require!(reference_hash.0.len() == 32, "Reference hash has to be 32 bytes");
+    }
+}
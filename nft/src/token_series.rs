@@ -1,6 +1,8 @@
 use near_contract_standards::non_fungible_token::metadata::TokenMetadata;
 
 use crate::common::*;
+use near_sdk::json_types::Base64VecU8;
+use serde_with::skip_serializing_none;
 
 use std::collections::HashMap;
 
@@ -8,6 +10,8 @@ use crate::token::TokenId;
 
 pub type TokenSeriesId = String;
 pub const TOKEN_DELIMETER: char = ':';
+// Minters are removed in one call on the ownership transfer, so their number is limited
+pub const MAX_SERIES_MINTERS: u64 = 50;
 
 // note, keep it all pub for now, but later switch to all private fields.
 
@@ -17,6 +21,31 @@ pub struct TokenSeries {
     pub owner_id: AccountId,
     pub tokens: UnorderedSet<TokenId>,
     pub royalty: HashMap<AccountId, u32>,
+    // Number of tokens ever minted, including burned ones
+    pub minted: u64,
+    // Frozen series can't be updated anymore
+    pub frozen: bool,
+    // Ownership transfer waiting to be accepted by the new owner
+    pub pending_owner: Option<PendingSeriesOwner>,
+    // Accounts allowed to mint the series besides the owner, ordered for cursor pagination
+    pub minters: TreeMap<AccountId, ()>,
+}
+
+impl TokenSeries {
+    pub fn is_minter(&self, account_id: &AccountId) -> bool {
+        &self.owner_id == account_id || self.minters.contains_key(account_id)
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub struct PendingSeriesOwner {
+    pub owner_id: AccountId,
+    // Whether the royalty of the current owner is moved to the new owner
+    pub move_royalty: bool,
+    // Whether the minters granted by the current owner can still mint the series
+    pub keep_minters: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -26,6 +55,25 @@ pub struct TokenSeriesJson {
     pub metadata: TokenMetadata,
     pub owner_id: AccountId,
     pub royalty: HashMap<AccountId, u32>,
+    pub frozen: bool,
+}
+
+// Changes to the series, fields that are not set stay the same.
+// `media` and `reference` are updated together with their hashes
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Default)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenSeriesUpdate {
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub media_hash: Option<Base64VecU8>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+    pub copies: Option<u64>,
+    pub royalty: Option<HashMap<AccountId, u32>>,
+    // Irreversibly forbids further updates
+    pub freeze: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -1,4 +1,5 @@
 use std::{
+    collections::HashMap,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
@@ -7,7 +8,10 @@ use near_units::{parse_gas, parse_near};
 use crate::utils::{init_market, init_nft, create_subaccount, create_series, deposit,
     mint_token, check_outcome_success, check_outcome_fail
 };
-use nft_bid_market::{ArgsKind, AuctionArgs, AuctionJson};
+use nft_bid_market::{
+    ArgsKind, AuctionArgs, AuctionIdJson, AuctionJson, KeeperBounty, Relist, SaleArgs, SaleJson,
+};
+use nft_contract::common::{U128, U64};
 //use workspaces::{Contract, Account, Worker};
 
 const THIRTY_SECONDS: Duration = Duration::from_secs(30);
@@ -54,6 +58,7 @@ async fn nft_on_approve_auction_positive() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(10000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -114,6 +119,7 @@ async fn auction_add_bid_negative() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(10000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -237,6 +243,7 @@ async fn auction_add_bid_positive() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(10000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -376,6 +383,7 @@ async fn cancel_auction_negative() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(10000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -497,6 +505,7 @@ async fn cancel_auction_positive() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(10000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -576,6 +585,7 @@ async fn finish_auction_positive() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(10000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -645,6 +655,7 @@ async fn finish_auction_negative() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(10000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -680,3 +691,424 @@ async fn finish_auction_negative() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/*
+    - Can only be called by the owner
+    - Panics if the bounty in bps exceeds the protocol fee
+    - Ended auction with a bid is returned by `get_unsettled_auctions`
+    - Anyone can finish the auction and get the bounty
+*/
+#[tokio::test]
+async fn finish_auction_keeper_bounty() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(
+        &worker,
+        worker.root_account().id(),
+        vec![nft.id()]
+    ).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+    let keeper = create_subaccount(&worker, &owner, "keeper").await?;
+
+    // Can only be called by the owner
+    let outcome = user1
+        .call(&worker, market.id().clone(), "set_keeper_bounty")
+        .args_json(serde_json::json!({
+            "keeper_bounty": KeeperBounty::Bps(100)
+        }))?
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "only owner can set the keeper bounty").await;
+
+    // Panics if the bounty in bps exceeds the protocol fee
+    let outcome = owner
+        .call(&worker, market.id().clone(), "set_keeper_bounty")
+        .args_json(serde_json::json!({
+            "keeper_bounty": KeeperBounty::Bps(400)
+        }))?
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Keeper bounty can't exceed the protocol fee").await;
+
+    let outcome = owner
+        .call(&worker, market.id().clone(), "set_keeper_bounty")
+        .args_json(serde_json::json!({
+            "keeper_bounty": KeeperBounty::Bps(100)
+        }))?
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let keeper_bounty: Option<KeeperBounty> = market
+        .view(&worker, "get_keeper_bounty", Vec::new())
+        .await?
+        .json()?;
+    assert_eq!(keeper_bounty, Some(KeeperBounty::Bps(100)));
+
+    let series = create_series(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        owner.id().clone()
+    ).await?;
+    let token1 = mint_token(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        user1.id(),
+        &series
+    ).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    user1
+        .call(&worker, nft.id().clone(), "nft_approve")
+        .args_json(serde_json::json!({
+            "token_id": token1,
+            "account_id": market.id(),
+            "msg": serde_json::json!(ArgsKind::Auction(AuctionArgs {
+                token_type: None,
+                minimal_step: 100.into(),
+                start_price: 10000.into(),
+                start: None,
+                duration: 900000000000.into(),
+                buy_out_price: Some(10000000000.into()),
+                origins: None,
+                allowed_buyers: None,
+            })).to_string()
+        }))?
+        .deposit(parse_near!("1 N"))
+        .gas(parse_gas!("200 Tgas") as u64)
+        .transact()
+        .await?;
+
+    // Buy out ends the auction
+    user2
+        .call(&worker, market.id().clone(), "auction_add_bid")
+        .args_json(serde_json::json!({
+            "auction_id": "0".to_string(),
+        }))?
+        .deposit(10300000000)
+        .transact()
+        .await?;
+    let unsettled: Vec<AuctionIdJson> = market
+        .view(&worker, "get_unsettled_auctions", serde_json::json!({}).to_string().into_bytes())
+        .await?
+        .json()?;
+    assert_eq!(unsettled.len(), 1);
+    assert_eq!(unsettled[0].auction_id, U128(0));
+
+    let outcome = keeper
+        .call(&worker, market.id().clone(), "finish_auction")
+        .args_json(serde_json::json!({
+            "auction_id": "0".to_string()
+        }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let unsettled: Vec<AuctionIdJson> = market
+        .view(&worker, "get_unsettled_auctions", serde_json::json!({}).to_string().into_bytes())
+        .await?
+        .json()?;
+    assert!(unsettled.is_empty());
+    Ok(())
+}
+
+/*
+- Panics if the auction is not active
+- Should panic if called before the auction ends
+- Removes the auction and releases the storage of its owner
+*/
+#[tokio::test]
+async fn remove_expired_auction() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+
+    let series = create_series(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        owner.id().clone()
+    ).await?;
+    let token1 = mint_token(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        user1.id(),
+        &series
+    ).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    user1
+        .call(&worker, nft.id().clone(), "nft_approve")
+        .args_json(serde_json::json!({
+            "token_id": token1,
+            "account_id": market.id(),
+            "msg": serde_json::json!(ArgsKind::Auction(AuctionArgs {
+                token_type: None,
+                minimal_step: 100.into(),
+                start_price: 10000.into(),
+                start: None,
+                duration: U64(THIRTY_SECONDS.as_nanos() as u64),
+                buy_out_price: None,
+                origins: None,
+                allowed_buyers: None,
+            })).to_string()
+        }))?
+        .deposit(parse_near!("1 N"))
+        .gas(parse_gas!("200 Tgas") as u64)
+        .transact()
+        .await?;
+    let supply: U64 = market
+        .view(
+            &worker,
+            "get_supply_auctions_by_owner_id",
+            serde_json::json!({ "account_id": user1.id() }).to_string().into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(supply, U64(1));
+
+    // Panics if the auction is not active
+    let outcome = user2
+        .call(&worker, market.id().clone(), "remove_expired_auction")
+        .args_json(serde_json::json!({ "auction_id": "1" }))?
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Auction is not active").await;
+
+    // Should panic if called before the auction ends
+    let outcome = user2
+        .call(&worker, market.id().clone(), "remove_expired_auction")
+        .args_json(serde_json::json!({ "auction_id": "0" }))?
+        .transact()
+        .await?;
+    check_outcome_fail(
+        outcome.status,
+        "Auction can be removed only after the end time",
+    )
+    .await;
+
+    tokio::time::sleep(THIRTY_SECONDS).await;
+    let outcome = user2
+        .call(&worker, market.id().clone(), "remove_expired_auction")
+        .args_json(serde_json::json!({ "auction_id": "0" }))?
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let auction = market
+        .view(
+            &worker,
+            "get_auction",
+            serde_json::json!({ "auction_id": "0" }).to_string().into_bytes(),
+        )
+        .await;
+    assert!(auction.is_err(), "The auction wasn't removed");
+    let supply: U64 = market
+        .view(
+            &worker,
+            "get_supply_auctions_by_owner_id",
+            serde_json::json!({ "account_id": user1.id() }).to_string().into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(supply, U64(0));
+    Ok(())
+}
+
+/*
+- Saves `relist` args of the bidder
+- Panics if the bidder hasn't paid storage for relist args
+- Panics if relist args are too long
+- Can only be called by the current bidder
+- Relist args can't be removed by others before the timeout
+- The winner lists the token again by approving it with an empty msg
+*/
+#[tokio::test]
+async fn relist_after_auction() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+
+    let series = create_series(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        owner.id().clone()
+    ).await?;
+    let token1 = mint_token(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        user1.id(),
+        &series
+    ).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    user1
+        .call(&worker, nft.id().clone(), "nft_approve")
+        .args_json(serde_json::json!({
+            "token_id": token1,
+            "account_id": market.id(),
+            "msg": serde_json::json!(ArgsKind::Auction(AuctionArgs {
+                token_type: None,
+                minimal_step: 100.into(),
+                start_price: 10000.into(),
+                start: None,
+                duration: 900000000000.into(),
+                buy_out_price: Some(10000000000.into()),
+                origins: None,
+                allowed_buyers: None,
+            })).to_string()
+        }))?
+        .deposit(parse_near!("1 N"))
+        .gas(parse_gas!("200 Tgas") as u64)
+        .transact()
+        .await?;
+
+    let relist_args = ArgsKind::Sale(SaleArgs {
+        sale_conditions: HashMap::from([("near".parse().unwrap(), 20000000000.into())]),
+        token_type: None,
+        start: None,
+        end: None,
+        origins: None,
+        allowed_buyers: None,
+        settle_at_end: None,
+    });
+    // Relist args take a slot of the bidder's storage deposit
+    let outcome = user2
+        .call(&worker, market.id().clone(), "auction_add_bid")
+        .args_json(serde_json::json!({
+            "auction_id": "0".to_string(),
+            "relist": relist_args,
+        }))?
+        .deposit(10300000000)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Insufficient storage paid for relist args").await;
+    deposit(&worker, market.id().clone(), &user2).await;
+
+    let long_relist_args = ArgsKind::Sale(SaleArgs {
+        sale_conditions: HashMap::from([("near".parse().unwrap(), 20000000000.into())]),
+        token_type: None,
+        start: None,
+        end: None,
+        origins: None,
+        allowed_buyers: Some(
+            (0..30)
+                .map(|i| format!("buyer{}.test.near", i).parse().unwrap())
+                .collect(),
+        ),
+        settle_at_end: None,
+    });
+    let outcome = user2
+        .call(&worker, market.id().clone(), "auction_add_bid")
+        .args_json(serde_json::json!({
+            "auction_id": "0".to_string(),
+            "relist": long_relist_args,
+        }))?
+        .deposit(10300000000)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Relist args are too long").await;
+
+    // Buy out ends the auction
+    let outcome = user2
+        .call(&worker, market.id().clone(), "auction_add_bid")
+        .args_json(serde_json::json!({
+            "auction_id": "0".to_string(),
+            "relist": relist_args,
+        }))?
+        .deposit(10300000000)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+
+    // Can only be called by the current bidder
+    let outcome = user1
+        .call(&worker, market.id().clone(), "set_auction_relist")
+        .args_json(serde_json::json!({
+            "auction_id": "0".to_string(),
+            "relist": null,
+        }))?
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Only the current bidder can set relist args").await;
+
+    let outcome = user2
+        .call(&worker, market.id().clone(), "finish_auction")
+        .args_json(serde_json::json!({
+            "auction_id": "0".to_string()
+        }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let relist: Option<Relist> = market
+        .view(
+            &worker,
+            "get_relist",
+            serde_json::json!({
+                "nft_contract_id": nft.id(),
+                "token_id": token1
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(relist.map(|r| r.owner_id), Some(user2.id().to_string().parse()?));
+
+    // Relist args can't be removed by others before the timeout
+    let outcome = user1
+        .call(&worker, market.id().clone(), "remove_expired_relist")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+        }))?
+        .transact()
+        .await?;
+    check_outcome_fail(
+        outcome.status,
+        "Relist args can be removed only after the timeout",
+    )
+    .await;
+
+    // The winner lists the token again by approving it with an empty msg
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_approve")
+        .args_json(serde_json::json!({
+            "token_id": token1,
+            "account_id": market.id(),
+            "msg": "",
+        }))?
+        .deposit(parse_near!("1 N"))
+        .gas(parse_gas!("200 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let sale: Option<SaleJson> = market
+        .view(
+            &worker,
+            "get_sale",
+            serde_json::json!({
+                "nft_contract_id": nft.id(),
+                "token_id": token1
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    let sale = sale.expect("The token wasn't relisted");
+    assert_eq!(sale.owner_id.to_string(), user2.id().to_string());
+    Ok(())
+}
@@ -6,7 +6,7 @@ use near_units::{parse_gas, parse_near};
 use crate::utils::{init_market, init_nft, create_subaccount, create_series, deposit,
     mint_token, check_outcome_success
 };
-use nft_bid_market::{ArgsKind, AuctionArgs, AuctionJson};
+use nft_bid_market::{ArgsKind, AuctionArgs, AuctionIdJson, AuctionJson, AuctionStatus, Page};
 use nft_contract::common::AccountId;
 use nft_contract::common::{U64, U128};
 
@@ -51,6 +51,7 @@ async fn view_auction_get_auction() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(10000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -160,6 +161,7 @@ async fn view_auction_get_auctions() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(10000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -179,6 +181,7 @@ async fn view_auction_get_auctions() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(1000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -259,6 +262,7 @@ async fn view_auction_get_current_buyer() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(10000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -370,6 +374,7 @@ async fn view_auction_get_current_bid() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(10000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -483,6 +488,7 @@ async fn view_auction_get_minimal_next_bid() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(10000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -599,6 +605,7 @@ async fn view_auction_check_auction_in_progress() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(10000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -658,6 +665,7 @@ async fn view_auction_check_auction_in_progress() -> anyhow::Result<()> {
                 duration: 900000000000.into(),
                 buy_out_price: Some(10000000000.into()),
                 origins: None,
+                allowed_buyers: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -679,4 +687,293 @@ async fn view_auction_check_auction_in_progress() -> anyhow::Result<()> {
     // TODO: check `check_auction_in_progress` if auction is ended
     
     Ok(())
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn view_auction_get_auctions_by_status() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(
+        &worker,
+        worker.root_account().id(),
+        vec![nft.id()]
+    ).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+
+    let series = create_series(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        owner.id().clone()
+    ).await?;
+    let token1 = mint_token(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        user1.id(),
+        &series
+    ).await?;
+    let token2 = mint_token(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        user1.id(),
+        &series
+    ).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+
+    // create an auction that starts now and one which starts one minute after now
+    let since_the_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards");
+    let waiting_time = Duration::from_secs(60);
+    let epoch_plus_waiting_time = (since_the_epoch + waiting_time).as_nanos();
+    for (token, start) in [(token1, None), (token2, Some(U64(epoch_plus_waiting_time as u64)))] {
+        user1
+            .call(&worker, nft.id().clone(), "nft_approve")
+            .args_json(serde_json::json!({
+                "token_id": token,
+                "account_id": market.id(),
+                "msg": serde_json::json!(ArgsKind::Auction(AuctionArgs {
+                    token_type: None,
+                    minimal_step: 100.into(),
+                    start_price: 10000.into(),
+                    start,
+                    duration: 900000000000.into(),
+                    buy_out_price: Some(10000000000.into()),
+                    origins: None,
+                    allowed_buyers: None,
+                })).to_string()
+            }))?
+            .deposit(parse_near!("1 N"))
+            .gas(parse_gas!("200 Tgas") as u64)
+            .transact()
+            .await?;
+    }
+
+    let live: Vec<AuctionIdJson> = market
+        .view(
+            &worker,
+            "get_auctions_by_status",
+            serde_json::json!({ "status": AuctionStatus::Live })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(live.len(), 1);
+    assert_eq!(live[0].auction_id, U128(0));
+    let upcoming: Vec<AuctionIdJson> = market
+        .view(
+            &worker,
+            "get_auctions_by_status",
+            serde_json::json!({ "status": AuctionStatus::Upcoming })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(upcoming.len(), 1);
+    assert_eq!(upcoming[0].auction_id, U128(1));
+    let ended: Vec<AuctionIdJson> = market
+        .view(
+            &worker,
+            "get_auctions_by_status",
+            serde_json::json!({ "status": AuctionStatus::Ended })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(ended.is_empty());
+
+    let live_page: Page<AuctionIdJson> = market
+        .view(
+            &worker,
+            "get_auctions_by_status_page",
+            serde_json::json!({ "status": AuctionStatus::Live, "limit": 1 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(live_page.items.len(), 1);
+    assert_eq!(live_page.items[0].auction_id, U128(0));
+    // The upcoming auction ends later, so it's skipped on the next page
+    let live_page: Page<AuctionIdJson> = market
+        .view(
+            &worker,
+            "get_auctions_by_status_page",
+            serde_json::json!({ "status": AuctionStatus::Live, "cursor": live_page.next_cursor })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(live_page.items.is_empty());
+    assert_eq!(live_page.next_cursor, None);
+    let private_page: Page<AuctionIdJson> = market
+        .view(
+            &worker,
+            "get_auctions_by_allowed_buyer_page",
+            serde_json::json!({ "account_id": user1.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(private_page.items.is_empty());
+    // An empty page can't be requested
+    let empty_page = market
+        .view(
+            &worker,
+            "get_auctions_page",
+            serde_json::json!({ "limit": 0 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await;
+    assert!(empty_page.is_err());
+
+    // Doesn't panic for unknown auctions
+    let statuses: Vec<Option<AuctionStatus>> = market
+        .view(
+            &worker,
+            "get_auction_statuses",
+            serde_json::json!({ "auction_ids": ["0", "1", "5"] })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(
+        statuses,
+        vec![Some(AuctionStatus::Live), Some(AuctionStatus::Upcoming), None]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn view_auction_get_auctions_by_allowed_buyer() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(
+        &worker,
+        worker.root_account().id(),
+        vec![nft.id()]
+    ).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+
+    let series = create_series(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        owner.id().clone()
+    ).await?;
+    let token1 = mint_token(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        user1.id(),
+        &series
+    ).await?;
+    let token2 = mint_token(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        user1.id(),
+        &series
+    ).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+
+    // The first auction is public, the second one is reserved for user2
+    let user2_id: AccountId = user2.id().to_string().parse()?;
+    for (token, allowed_buyers) in [(token1, None), (token2, Some(vec![user2_id.clone()]))] {
+        let outcome = user1
+            .call(&worker, nft.id().clone(), "nft_approve")
+            .args_json(serde_json::json!({
+                "token_id": token,
+                "account_id": market.id(),
+                "msg": serde_json::json!(ArgsKind::Auction(AuctionArgs {
+                    token_type: None,
+                    minimal_step: 100.into(),
+                    start_price: 10000.into(),
+                    start: None,
+                    duration: 900000000000.into(),
+                    buy_out_price: Some(10000000000.into()),
+                    origins: None,
+                    allowed_buyers,
+                })).to_string()
+            }))?
+            .deposit(parse_near!("1 N"))
+            .gas(parse_gas!("200 Tgas") as u64)
+            .transact()
+            .await?;
+        check_outcome_success(outcome.status).await;
+    }
+
+    let auctions: Vec<AuctionJson> = market
+        .view(
+            &worker,
+            "get_auctions_by_allowed_buyer",
+            serde_json::json!({ "account_id": user2.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(auctions.len(), 1);
+    assert_eq!(auctions[0].allowed_buyers, Some(vec![user2_id.clone()]));
+    let page: Page<AuctionIdJson> = market
+        .view(
+            &worker,
+            "get_auctions_by_allowed_buyer_page",
+            serde_json::json!({ "account_id": user2.id(), "limit": 1 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.items[0].auction_id, U128(1));
+    assert_eq!(page.next_cursor, None);
+
+    // The removed auction is removed from the index
+    let outcome = user1
+        .call(&worker, market.id().clone(), "cancel_auction")
+        .args_json(serde_json::json!({
+            "auction_id": "1".to_string()
+        }))?
+        .deposit(1)
+        .gas(parse_gas!("200 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let auctions: Vec<AuctionJson> = market
+        .view(
+            &worker,
+            "get_auctions_by_allowed_buyer",
+            serde_json::json!({ "account_id": user2.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(auctions.is_empty());
+    let page: Page<AuctionIdJson> = market
+        .view(
+            &worker,
+            "get_auctions_by_allowed_buyer_page",
+            serde_json::json!({ "account_id": user2.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(page.items.is_empty());
+    Ok(())
+}
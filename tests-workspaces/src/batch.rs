@@ -0,0 +1,188 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::utils::{
+    check_outcome_success, create_series, create_subaccount, deposit, init_market, init_nft,
+    mint_token,
+};
+use near_units::{parse_gas, parse_near};
+use nft_bid_market::{ArgsKind, AuctionArgs, AuctionIdJson, SaleArgs, SaleJson};
+use nft_contract::common::U64;
+
+/*
+- Refunds and removes expired bids
+- Removes finished sales without bids
+- Returns `None` when the end of the list is reached
+*/
+#[tokio::test]
+async fn batch_cleanup() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+
+    let series = create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    let since_the_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards");
+    let waiting_time = Duration::from_secs(15);
+    let epoch_plus_waiting_time = (since_the_epoch + waiting_time).as_nanos();
+    user1
+        .call(&worker, nft.id().clone(), "nft_approve")
+        .args_json(serde_json::json!({
+            "token_id": token1,
+            "account_id": market.id(),
+            "msg": serde_json::json!(ArgsKind::Sale(SaleArgs {
+                sale_conditions: HashMap::from([("near".parse().unwrap(), 10000.into())]),
+                token_type: Some(series),
+                start: None,
+                end: Some(U64(epoch_plus_waiting_time as u64)),
+                origins: None,
+                allowed_buyers: None,
+                settle_at_end: None,
+            })).to_string()
+        }))?
+        .deposit(parse_near!("1 N"))
+        .gas(parse_gas!("200 Tgas") as u64)
+        .transact()
+        .await?;
+    user2
+        .call(&worker, market.id().clone(), "offer")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+            "duration": "1",
+        }))?
+        .deposit(900)
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+
+    // Refunds and removes expired bids
+    let outcome = user2
+        .call(&worker, market.id().clone(), "purge_expired_bids")
+        .args_json(serde_json::json!({ "limit": 10 }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status.clone()).await;
+    let cursor: Option<U64> = outcome.json()?;
+    assert!(cursor.is_none());
+    let sale: Option<SaleJson> = market
+        .view(
+            &worker,
+            "get_sale",
+            serde_json::json!({
+                "nft_contract_id": nft.id(),
+                "token_id": token1
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(sale.unwrap().bids.is_empty(), "Expired bid wasn't removed");
+
+    // Removes finished sales without bids
+    tokio::time::sleep(waiting_time).await;
+    let outcome = user2
+        .call(&worker, market.id().clone(), "remove_expired_sales")
+        .args_json(serde_json::json!({ "from_index": "0", "limit": 10 }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status.clone()).await;
+    let cursor: Option<U64> = outcome.json()?;
+    assert!(cursor.is_none());
+    let supply: U64 = market
+        .view(&worker, "get_supply_sales", Vec::new())
+        .await?
+        .json()?;
+    assert_eq!(supply, U64(0));
+
+    // Nothing to finish
+    let cursor: Option<U64> = user2
+        .call(&worker, market.id().clone(), "finish_auctions")
+        .args_json(serde_json::json!({ "limit": 10 }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?
+        .json()?;
+    assert!(cursor.is_none());
+    Ok(())
+}
+
+/*
+- `finish_auctions` finishes several auctions in one call with 300 TGas
+- Returns `None` when all auctions are finished
+*/
+#[tokio::test]
+async fn finish_auctions_gas() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+
+    let series = create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    for auction_id in 0..3 {
+        let token_id = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+        user1
+            .call(&worker, nft.id().clone(), "nft_approve")
+            .args_json(serde_json::json!({
+                "token_id": token_id,
+                "account_id": market.id(),
+                "msg": serde_json::json!(ArgsKind::Auction(AuctionArgs {
+                    token_type: None,
+                    minimal_step: 100.into(),
+                    start_price: 10000.into(),
+                    start: None,
+                    duration: 900000000000.into(),
+                    buy_out_price: Some(10000000000.into()),
+                    origins: None,
+                    allowed_buyers: None,
+                })).to_string()
+            }))?
+            .deposit(parse_near!("1 N"))
+            .gas(parse_gas!("200 Tgas") as u64)
+            .transact()
+            .await?;
+        // Buy out ends the auction
+        let outcome = user2
+            .call(&worker, market.id().clone(), "auction_add_bid")
+            .args_json(serde_json::json!({
+                "auction_id": auction_id.to_string(),
+            }))?
+            .deposit(10300000000)
+            .transact()
+            .await?;
+        check_outcome_success(outcome.status).await;
+    }
+
+    let outcome = user2
+        .call(&worker, market.id().clone(), "finish_auctions")
+        .args_json(serde_json::json!({ "limit": 10 }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status.clone()).await;
+    let cursor: Option<U64> = outcome.json()?;
+    assert!(cursor.is_none());
+    let unsettled: Vec<AuctionIdJson> = market
+        .view(&worker, "get_unsettled_auctions", serde_json::json!({}).to_string().into_bytes())
+        .await?
+        .json()?;
+    assert!(unsettled.is_empty());
+    Ok(())
+}
@@ -4,8 +4,8 @@ use crate::utils::{
     check_outcome_fail, check_outcome_success, create_series, create_subaccount, deposit,
     init_market, init_nft, mint_token, nft_approve, offer, offer_with_duration,
 };
-use near_units::parse_gas;
-use nft_bid_market::SaleJson;
+use near_units::{parse_gas, parse_near};
+use nft_bid_market::{ArgsKind, AuctionArgs, AuctionIdJson, Page, SaleJson};
 use nft_contract::common::{AccountId, U128, U64};
 
 /*
@@ -736,3 +736,176 @@ async fn cancel_expired_bids_negative() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/*
+- Sale is added to the bidder index when the bid is made
+- Auction is added to the bidder index when the bid is made, removed when the bid is outbid
+- Escrowed amount is the total of the account bids in each ft token
+- Page views return the same sales and auctions of the bidder
+*/
+#[tokio::test]
+async fn bids_by_bidder() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+    let user3 = create_subaccount(&worker, &owner, "user3").await?;
+
+    let series = create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    let token2 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    let sale_conditions = HashMap::from([("near".parse().unwrap(), 10000.into())]);
+    nft_approve(
+        &worker,
+        nft.id().clone(),
+        market.id().clone(),
+        &user1,
+        token1.clone(),
+        sale_conditions,
+        series,
+    )
+    .await;
+    offer(
+        &worker,
+        nft.id().clone(),
+        market.id().clone(),
+        &user2,
+        token1.clone(),
+        900.into(),
+    )
+    .await;
+    user1
+        .call(&worker, nft.id().clone(), "nft_approve")
+        .args_json(serde_json::json!({
+            "token_id": token2,
+            "account_id": market.id(),
+            "msg": serde_json::json!(ArgsKind::Auction(AuctionArgs {
+                token_type: None,
+                minimal_step: 100.into(),
+                start_price: 10000.into(),
+                start: None,
+                duration: 900000000000.into(),
+                buy_out_price: None,
+                origins: None,
+                allowed_buyers: None,
+            })).to_string()
+        }))?
+        .deposit(parse_near!("1 N"))
+        .gas(parse_gas!("200 Tgas") as u64)
+        .transact()
+        .await?;
+    let outcome = user2
+        .call(&worker, market.id().clone(), "auction_add_bid")
+        .args_json(serde_json::json!({ "auction_id": "0" }))?
+        .deposit(10300)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+
+    let sales: Vec<SaleJson> = market
+        .view(
+            &worker,
+            "get_sales_by_bidder",
+            serde_json::json!({ "account_id": user2.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(sales.len(), 1);
+    assert_eq!(sales[0].token_id, token1);
+    let auctions: Vec<AuctionIdJson> = market
+        .view(
+            &worker,
+            "get_auctions_by_bidder",
+            serde_json::json!({ "account_id": user2.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(auctions.len(), 1);
+    let sales_page: Page<SaleJson> = market
+        .view(
+            &worker,
+            "get_sales_by_bidder_page",
+            serde_json::json!({ "account_id": user2.id(), "limit": 1 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(sales_page.items.len(), 1);
+    assert_eq!(sales_page.items[0].token_id, token1);
+    assert_eq!(sales_page.next_cursor, None);
+    let auctions_page: Page<AuctionIdJson> = market
+        .view(
+            &worker,
+            "get_auctions_by_bidder_page",
+            serde_json::json!({ "account_id": user2.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(auctions_page.items.len(), 1);
+    assert_eq!(auctions_page.items[0].auction_id, U128(0));
+    let escrowed: HashMap<AccountId, U128> = market
+        .view(
+            &worker,
+            "get_escrowed_by_bidder",
+            serde_json::json!({ "account_id": user2.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(escrowed.get(&"near".parse().unwrap()), Some(&U128(900 + 10300)));
+
+    // The outbid bidder is removed from the auction index
+    let outcome = user3
+        .call(&worker, market.id().clone(), "auction_add_bid")
+        .args_json(serde_json::json!({ "auction_id": "0" }))?
+        .deposit(10500)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let supply: U64 = market
+        .view(
+            &worker,
+            "get_supply_auctions_by_bidder",
+            serde_json::json!({ "account_id": user2.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(supply, U64(0));
+    let supply: U64 = market
+        .view(
+            &worker,
+            "get_supply_auctions_by_bidder",
+            serde_json::json!({ "account_id": user3.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(supply, U64(1));
+    let auctions_page: Page<AuctionIdJson> = market
+        .view(
+            &worker,
+            "get_auctions_by_bidder_page",
+            serde_json::json!({ "account_id": user2.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(auctions_page.items.is_empty());
+    Ok(())
+}
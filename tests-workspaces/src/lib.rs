@@ -10,4 +10,11 @@ mod permissions;
 mod auction_views;
 mod sale_views;
 mod series_views;
+mod series_management;
+mod public_mint;
+mod presale;
 mod fee;
+mod moderation;
+mod batch;
+mod price_history;
+mod stats;
@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use crate::utils::{
+    check_outcome_fail, check_outcome_success, create_series, create_subaccount, deposit,
+    init_market, init_nft, mint_token, nft_approve, offer,
+};
+use near_units::{parse_gas, parse_near};
+use nft_bid_market::{ArgsKind, SaleArgs, SaleJson};
+
+/*
+- Can only be called by the owner
+- Returns `true` if the new account has been added to the list, `false` otherwise
+- Moderator can block accounts, blocked accounts can't list or bid
+*/
+#[tokio::test]
+async fn moderation_block_account() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+    let moderator = create_subaccount(&worker, &owner, "moderator").await?;
+
+    // Can only be called by the owner
+    let outcome = user1
+        .call(&worker, market.id().clone(), "add_moderator")
+        .args_json(serde_json::json!({ "account_id": moderator.id() }))?
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "only owner can add moderators").await;
+
+    let added: bool = owner
+        .call(&worker, market.id().clone(), "add_moderator")
+        .args_json(serde_json::json!({ "account_id": moderator.id() }))?
+        .transact()
+        .await?
+        .json()?;
+    assert!(added);
+    let added: bool = owner
+        .call(&worker, market.id().clone(), "add_moderator")
+        .args_json(serde_json::json!({ "account_id": moderator.id() }))?
+        .transact()
+        .await?
+        .json()?;
+    assert!(!added);
+
+    // Only moderators can block
+    let outcome = user1
+        .call(&worker, market.id().clone(), "block_account")
+        .args_json(serde_json::json!({ "account_id": user2.id() }))?
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Only moderators can call this method").await;
+
+    let series = create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    let sale_conditions = HashMap::from([("near".parse().unwrap(), 10000.into())]);
+    nft_approve(
+        &worker,
+        nft.id().clone(),
+        market.id().clone(),
+        &user1,
+        token1.clone(),
+        sale_conditions,
+        series.clone(),
+    )
+    .await;
+
+    // Blocked account can't bid
+    let outcome = moderator
+        .call(&worker, market.id().clone(), "block_account")
+        .args_json(serde_json::json!({ "account_id": user2.id() }))?
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let outcome = user2
+        .call(&worker, market.id().clone(), "offer")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+        }))?
+        .deposit(900)
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "is blocked").await;
+
+    // Blocked account can't list
+    let token2 = mint_token(&worker, nft.id().clone(), &user1, user2.id(), &series).await?;
+    deposit(&worker, market.id().clone(), &user2).await;
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_approve")
+        .args_json(serde_json::json!({
+            "token_id": token2,
+            "account_id": market.id(),
+            "msg": serde_json::json!(ArgsKind::Sale(SaleArgs {
+                sale_conditions: HashMap::from([("near".parse().unwrap(), 10000.into())]),
+                token_type: Some(series),
+                start: None,
+                end: None,
+                origins: None,
+                allowed_buyers: None,
+                settle_at_end: None,
+            })).to_string()
+        }))?
+        .deposit(parse_near!("1 N"))
+        .gas(parse_gas!("200 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "is blocked").await;
+    Ok(())
+}
+
+/*
+- Should panic unless 1 yoctoNEAR is attached
+- Can only be called by a moderator
+- Removes the sale even if it is in progress
+- Refunds all bids
+*/
+#[tokio::test]
+async fn moderator_remove_sale() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+
+    let series = create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    let sale_conditions = HashMap::from([("near".parse().unwrap(), 10000.into())]);
+    nft_approve(
+        &worker,
+        nft.id().clone(),
+        market.id().clone(),
+        &user1,
+        token1.clone(),
+        sale_conditions,
+        series,
+    )
+    .await;
+    offer(
+        &worker,
+        nft.id().clone(),
+        market.id().clone(),
+        &user2,
+        token1.clone(),
+        900.into(),
+    )
+    .await;
+
+    // Should panic unless 1 yoctoNEAR is attached
+    let outcome = owner
+        .call(&worker, market.id().clone(), "moderator_remove_sale")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+        }))?
+        .transact()
+        .await?;
+    check_outcome_fail(
+        outcome.status,
+        "Requires attached deposit of exactly 1 yoctoNEAR",
+    )
+    .await;
+
+    // Can only be called by a moderator
+    let outcome = user2
+        .call(&worker, market.id().clone(), "moderator_remove_sale")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+        }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Only moderators can call this method").await;
+
+    // Owner of the market is a moderator
+    let outcome = owner
+        .call(&worker, market.id().clone(), "moderator_remove_sale")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+        }))?
+        .deposit(1)
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let sale: Option<SaleJson> = market
+        .view(
+            &worker,
+            "get_sale",
+            serde_json::json!({
+                "nft_contract_id": nft.id(),
+                "token_id": token1
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(sale.is_none(), "The sale wasn't removed");
+    Ok(())
+}
@@ -1,12 +1,12 @@
 use std::collections::HashMap;
 
 use crate::utils::{
-    check_outcome_fail, create_series_raw, init_nft, mint_token,
+    check_outcome_fail, check_outcome_success, create_series_raw, init_nft, mint_token,
     nft_transfer_payout_helper,
 };
 use near_contract_standards::non_fungible_token::{metadata::TokenMetadata, Token};
 use near_units::{parse_gas, parse_near};
-use nft_bid_market::Fees;
+use nft_contract::common::U128;
 use nft_contract::TokenSeriesJson;
 
 /*
@@ -446,12 +446,528 @@ async fn nft_mint_positive() -> anyhow::Result<()> {
     Ok(())
 }
 
+/*
+- Panics if a hash of `token_metadata` is set without its url or isn't 32 bytes
+- `token_metadata` fields are merged over the series metadata
+*/
+#[tokio::test]
+async fn nft_mint_token_metadata() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = owner
+        .create_subaccount(&worker, "user1")
+        .initial_balance(parse_near!("10 N"))
+        .transact()
+        .await?
+        .unwrap();
+    let series_id: String = user1
+        .call(&worker, nft.id().clone(), "nft_create_series")
+        .args_json(serde_json::json!({
+            "token_metadata": {
+                "title": "some title",
+                "description": "series description",
+                "media": "ipfs://QmTqZsmhZLLbi8vxZwm21wjKRFRBUQFzMFtTiyh3DJ2CCz",
+                "reference": "ipfs://series_reference",
+                "copies": 10
+            },
+            "royalty": null
+        }))?
+        .deposit(parse_near!("0.005 N"))
+        .transact()
+        .await?
+        .json()?;
+
+    // Media hash has to be 32 bytes
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series_id,
+            "receiver_id": user1.id(),
+            "token_metadata": {
+                "media": "ipfs://token_media",
+                "media_hash": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=="
+            }
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Media hash has to be 32 bytes").await;
+
+    // Reference hash requires reference
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series_id,
+            "receiver_id": user1.id(),
+            "token_metadata": {
+                "reference_hash": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA="
+            }
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Reference hash requires reference").await;
+
+    let token_id: String = user1
+        .call(&worker, nft.id().clone(), "nft_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series_id,
+            "receiver_id": user1.id(),
+            "token_metadata": {
+                "title": "Edition #1",
+                "media": "ipfs://token_media",
+                "media_hash": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=",
+                "extra": "{\"rarity\":\"legendary\"}",
+                "copies": 42
+            }
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?
+        .json()?;
+    let token: Token = nft
+        .view(
+            &worker,
+            "nft_token",
+            serde_json::json!({ "token_id": token_id })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    let metadata = token.metadata.unwrap();
+    assert_eq!(metadata.title, Some("Edition #1".to_string()));
+    assert_eq!(metadata.description, Some("series description".to_string()));
+    assert_eq!(metadata.media, Some("ipfs://token_media".to_string()));
+    assert_eq!(metadata.media_hash.map(|hash| hash.0), Some(vec![0; 32]));
+    assert_eq!(metadata.extra, Some("{\"rarity\":\"legendary\"}".to_string()));
+    assert_eq!(metadata.reference, Some("ipfs://series_reference".to_string()));
+    assert_eq!(metadata.copies, None);
+    assert!(metadata.issued_at.is_some());
+
+    // Token without overrides has the series metadata
+    let token_id = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series_id).await?;
+    let token: Token = nft
+        .view(
+            &worker,
+            "nft_token",
+            serde_json::json!({ "token_id": token_id })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    let metadata = token.metadata.unwrap();
+    assert_eq!(metadata.title, Some("some title".to_string()));
+    assert_eq!(
+        metadata.media,
+        Some("ipfs://QmTqZsmhZLLbi8vxZwm21wjKRFRBUQFzMFtTiyh3DJ2CCz".to_string())
+    );
+    assert_eq!(metadata.media_hash, None);
+    Ok(())
+}
+
+/*
+- Can only be called by the owner of the series
+- Panics if nothing to mint
+- Panics if the batch exceeds the maximum number of tokens
+*/
+#[tokio::test]
+async fn nft_batch_mint_negative() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = owner
+        .create_subaccount(&worker, "user1")
+        .initial_balance(parse_near!("10 N"))
+        .transact()
+        .await?
+        .unwrap();
+    let user2 = owner
+        .create_subaccount(&worker, "user2")
+        .initial_balance(parse_near!("10 N"))
+        .transact()
+        .await?
+        .unwrap();
+    let series =
+        create_series_raw(&worker, nft.id().clone(), &user1, Some(4), HashMap::new()).await?;
+
+    // only owner can mint
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_batch_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "receivers": [[user2.id(), 1]],
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "permission denied").await;
+
+    // nothing to mint
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_batch_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "receivers": [[user2.id(), 0]],
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Nothing to mint").await;
+
+    // exceed max tokens, including already minted
+    mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_batch_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "receivers": [[user1.id(), 2], [user2.id(), 2]],
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Max token minted").await;
+    Ok(())
+}
+
+/*
+- Mints `count` tokens to each receiver
+- Refunds a deposit
+*/
+#[tokio::test]
+async fn nft_batch_mint_positive() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = owner
+        .create_subaccount(&worker, "user1")
+        .initial_balance(parse_near!("10 N"))
+        .transact()
+        .await?
+        .unwrap();
+    let user2 = owner
+        .create_subaccount(&worker, "user2")
+        .initial_balance(parse_near!("10 N"))
+        .transact()
+        .await?
+        .unwrap();
+    let series =
+        create_series_raw(&worker, nft.id().clone(), &user1, Some(4), HashMap::new()).await?;
+    mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+
+    let token_ids: Vec<String> = user1
+        .call(&worker, nft.id().clone(), "nft_batch_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "receivers": [[user1.id(), 1], [user2.id(), 2]],
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(
+        token_ids,
+        vec![
+            format!("{}:2", series),
+            format!("{}:3", series),
+            format!("{}:4", series)
+        ]
+    );
+
+    let tokens: Vec<Token> = nft
+        .view(
+            &worker,
+            "nft_tokens_for_owner",
+            serde_json::json!({ "account_id": user2.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(tokens.len(), 2);
+    let supply: U128 = nft
+        .view(
+            &worker,
+            "nft_supply_for_series",
+            serde_json::json!({ "token_series_id": series })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(supply.0, 4);
+    Ok(())
+}
+
+/*
+- 50 tokens fit into a single call with 300 TGas
+- 1000 tokens exceed the prepaid gas, such drops should be split into several calls
+*/
+#[tokio::test]
+async fn nft_batch_mint_gas() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = owner
+        .create_subaccount(&worker, "user1")
+        .initial_balance(parse_near!("10 N"))
+        .transact()
+        .await?
+        .unwrap();
+    let series =
+        create_series_raw(&worker, nft.id().clone(), &user1, None, HashMap::new()).await?;
+
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_batch_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "receivers": [[user1.id(), 50]],
+        }))?
+        .deposit(parse_near!("2 N"))
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_batch_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "receivers": [[user1.id(), 1000]],
+        }))?
+        .deposit(parse_near!("5 N"))
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Exceeded the prepaid gas").await;
+    Ok(())
+}
+
+/*
+- Should panic unless 1 yoctoNEAR is attached
+- Panics if the token doesn't exist
+- Can only be called by the owner or an approved account
+*/
+#[tokio::test]
+async fn nft_burn_negative() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = owner
+        .create_subaccount(&worker, "user1")
+        .initial_balance(parse_near!("10 N"))
+        .transact()
+        .await?
+        .unwrap();
+    let user2 = owner
+        .create_subaccount(&worker, "user2")
+        .initial_balance(parse_near!("10 N"))
+        .transact()
+        .await?
+        .unwrap();
+    let series =
+        create_series_raw(&worker, nft.id().clone(), &user1, Some(4), HashMap::new()).await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+
+    // 1 yoctoNEAR not attached
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_burn")
+        .args_json(serde_json::json!({ "token_id": token1 }))?
+        .transact()
+        .await?;
+    check_outcome_fail(
+        outcome.status,
+        "Requires attached deposit of exactly 1 yoctoNEAR",
+    )
+    .await;
+
+    // token doesn't exist
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_burn")
+        .args_json(serde_json::json!({ "token_id": "1:42" }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Token not found").await;
+
+    // neither owner nor approved
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_burn")
+        .args_json(serde_json::json!({ "token_id": token1 }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Sender not approved").await;
+    Ok(())
+}
+
+/*
+- Removes the token and refunds the freed storage to the owner
+- Series supply doesn't include burned tokens
+- Ids of burned tokens are not reused
+*/
+#[tokio::test]
+async fn nft_burn_positive() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = owner
+        .create_subaccount(&worker, "user1")
+        .initial_balance(parse_near!("10 N"))
+        .transact()
+        .await?
+        .unwrap();
+    let user2 = owner
+        .create_subaccount(&worker, "user2")
+        .initial_balance(parse_near!("10 N"))
+        .transact()
+        .await?
+        .unwrap();
+    let series =
+        create_series_raw(&worker, nft.id().clone(), &user1, Some(4), HashMap::new()).await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    let token2 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+
+    // Owner burns the token
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_burn")
+        .args_json(serde_json::json!({ "token_id": token1 }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let token: Option<Token> = nft
+        .view(
+            &worker,
+            "nft_token",
+            serde_json::json!({ "token_id": token1 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(token.is_none());
+
+    // Approved account burns the token
+    user1
+        .call(&worker, nft.id().clone(), "nft_approve")
+        .args_json(serde_json::json!({
+            "token_id": token2,
+            "account_id": user2.id(),
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_burn")
+        .args_json(serde_json::json!({ "token_id": token2, "approval_id": 0 }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+
+    let supply: U128 = nft
+        .view(
+            &worker,
+            "nft_supply_for_series",
+            serde_json::json!({ "token_series_id": series })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(supply.0, 0);
+    let tokens: Vec<Token> = nft
+        .view(
+            &worker,
+            "nft_tokens_for_owner",
+            serde_json::json!({ "account_id": user1.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(tokens.is_empty());
+
+    let token3 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    assert_eq!(token3, format!("{}:3", series));
+    Ok(())
+}
+
+/*
+- Token is returned to the previous owner if the receiver doesn't implement `nft_on_transfer`
+- Approvals of the token are restored after the return
+*/
+#[tokio::test]
+async fn nft_transfer_call_revert() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = owner
+        .create_subaccount(&worker, "user1")
+        .initial_balance(parse_near!("10 N"))
+        .transact()
+        .await?
+        .unwrap();
+    let user2 = owner
+        .create_subaccount(&worker, "user2")
+        .initial_balance(parse_near!("10 N"))
+        .transact()
+        .await?
+        .unwrap();
+    let series =
+        create_series_raw(&worker, nft.id().clone(), &user1, Some(4), HashMap::new()).await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    user1
+        .call(&worker, nft.id().clone(), "nft_approve")
+        .args_json(serde_json::json!({
+            "token_id": token1,
+            "account_id": user2.id(),
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+
+    // `user2` has no contract, so `nft_on_transfer` fails
+    let transferred: bool = user1
+        .call(&worker, nft.id().clone(), "nft_transfer_call")
+        .args_json(serde_json::json!({
+            "receiver_id": user2.id(),
+            "token_id": token1,
+            "memo": "transfer memo",
+            "msg": "",
+        }))?
+        .deposit(1)
+        .gas(parse_gas!("100 Tgas") as u64)
+        .transact()
+        .await?
+        .json()?;
+    assert!(!transferred);
+    // The transfer and revert events are checked in market/tests/nft_contract.rs
+    let token: Token = nft
+        .view(
+            &worker,
+            "nft_token",
+            serde_json::json!({ "token_id": token1 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(token.owner_id.as_str(), user1.id().as_ref());
+    assert!(token
+        .approved_account_ids
+        .unwrap()
+        .contains_key(&user2.id().as_ref().parse().unwrap()));
+    Ok(())
+}
+
 /*
 - Should panic unless 1 yoctoNEAR is attached
 - Panics if `token_id` which doesn't exist
-- Panics if the number of royalties exceeds `max_len_payout`
-- Panics if invalid `memo` is provided
-- Panics if total payout exceeds `ROYALTY_TOTAL_VALUE`
+- Panics if the number of royalties together with the owner exceeds `max_len_payout`
 */
 #[tokio::test]
 async fn nft_transfer_payout_negative() -> anyhow::Result<()> {
@@ -570,59 +1086,44 @@ async fn nft_transfer_payout_negative() -> anyhow::Result<()> {
         .await?;
     check_outcome_fail(outcome.status, "Too many recievers").await;
 
-    // invalid `memo` is provided
-    let outcome = user2
-        .call(&worker, nft.id().clone(), "nft_transfer_payout")
-        .args_json(serde_json::json!({
-            "receiver_id": user3.id(),
-            "token_id": token1,
-            "approval_id": approval_id,
-            "memo": "some_wrong_memo",
-            "balance": "10000",
-            "max_len_payout": 10,
-        }))?
-        .deposit(1)
-        .transact()
-        .await?;
-    check_outcome_fail(outcome.status, "invalid FeesArgs").await;
-
-    // if total payout exceeds `ROYALTY_TOTAL_VALUE`
-    let fees = Fees {
-        buyer: HashMap::from([
-            ("acc1.near".parse().unwrap(), 100),
-            ("acc2.near".parse().unwrap(), 100),
-            ("acc3.near".parse().unwrap(), 100),
-            ("acc4.near".parse().unwrap(), 100),
-            ("acc5.near".parse().unwrap(), 100),
-            ("acc6.near".parse().unwrap(), 100),
-        ]),
-        seller: HashMap::from([
-            ("acc7.near".parse().unwrap(), 100),
-            ("acc8.near".parse().unwrap(), 100),
-            ("acc9.near".parse().unwrap(), 100),
-            ("acc10.near".parse().unwrap(), 100),
-            ("acc11.near".parse().unwrap(), 100),
-            ("acc12.near".parse().unwrap(), 100),
+    // the owner isn't among 6 royalties, so the payout has 7 accounts
+    let series = create_series_raw(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        Some(4),
+        HashMap::from([
+            (&"acc1.near".parse().unwrap(), 100),
+            (&"acc2.near".parse().unwrap(), 100),
+            (&"acc3.near".parse().unwrap(), 100),
+            (&"acc4.near".parse().unwrap(), 100),
+            (&"acc5.near".parse().unwrap(), 100),
+            (&"acc6.near".parse().unwrap(), 100),
         ]),
-    };
-    let outcome = user2
+    )
+    .await?;
+    let token2 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    let outcome = user1
         .call(&worker, nft.id().clone(), "nft_transfer_payout")
         .args_json(serde_json::json!({
             "receiver_id": user3.id(),
-            "token_id": token1,
-            "approval_id": approval_id,
-            "memo": serde_json::json!(fees).to_string(),
+            "token_id": token2,
+            "approval_id": 0,
             "balance": "10000",
-            "max_len_payout": 10,
+            "max_len_payout": 6,
         }))?
         .deposit(1)
         .transact()
         .await?;
     check_outcome_fail(outcome.status, "Too many recievers").await;
+
     Ok(())
 }
 
-// - Returns payout, which contains royalties and payouts from `memo`
+/*
+- Returns payout, which contains only royalties and the owner
+- Accepts arbitrary `memo`
+*/
 // Checking calculations here
 #[tokio::test]
 async fn nft_transfer_payout_positive() -> anyhow::Result<()> {
@@ -656,18 +1157,20 @@ async fn nft_transfer_payout_positive() -> anyhow::Result<()> {
         &user1,
         &user2,
         &user3,
-        HashMap::from([(user1.id(), 500)]),
-        Fees {
-            buyer: HashMap::from([(user2.id().as_ref().parse().unwrap(), 300)]),
-            seller: HashMap::from([(user2.id().as_ref().parse().unwrap(), 300)]),
-        },
+        HashMap::from([(user2.id(), 500)]),
+        None,
         parsed_near,
     )
     .await;
-
     let sum: u128 = payouts.payout.values().map(|val| val.0).sum();
     assert!(parsed_near.0 - sum <= 1);
+    assert_eq!(payouts.payout.len(), 2);
+    assert_eq!(
+        payouts.payout[&user2.id().as_ref().parse().unwrap()],
+        U128(parse_near!("0.1005 N"))
+    );
 
+    // `memo` is not parsed by the contract
     let parsed_near = parse_near!("1.23 N").into();
     let payouts = nft_transfer_payout_helper(
         &worker,
@@ -675,18 +1178,16 @@ async fn nft_transfer_payout_positive() -> anyhow::Result<()> {
         &user1,
         &user2,
         &user3,
-        HashMap::from([(user1.id(), 500)]),
-        Fees {
-            buyer: HashMap::from([(user2.id().as_ref().parse().unwrap(), 300)]),
-            seller: HashMap::from([(user2.id().as_ref().parse().unwrap(), 300)]),
-        },
+        HashMap::from([(user2.id(), 500)]),
+        Some("{\"buyer\": {}, \"seller\": {}}".to_string()),
         parsed_near,
     )
     .await;
-
     let sum: u128 = payouts.payout.values().map(|val| val.0).sum();
     assert!(parsed_near.0 - sum <= 1);
+    assert_eq!(payouts.payout.len(), 2);
 
+    // Royalty of the owner is a part of its payout
     let parsed_near = parse_near!("3.45 N").into();
     let payouts = nft_transfer_payout_helper(
         &worker,
@@ -695,15 +1196,13 @@ async fn nft_transfer_payout_positive() -> anyhow::Result<()> {
         &user2,
         &user3,
         HashMap::from([(user1.id(), 500)]),
-        Fees {
-            buyer: HashMap::from([(user2.id().as_ref().parse().unwrap(), 300), (user1.id().as_ref().parse().unwrap(), 500)]),
-            seller: HashMap::from([(user2.id().as_ref().parse().unwrap(), 300)]),
-        },
+        Some("some memo".to_string()),
         parsed_near,
     )
     .await;
-
-    let sum: u128 = payouts.payout.values().map(|val| val.0).sum();
-    assert!(parsed_near.0 - sum <= 1);
+    assert_eq!(
+        payouts.payout,
+        HashMap::from([(user1.id().as_ref().parse().unwrap(), parsed_near)])
+    );
     Ok(())
 }
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use crate::utils::{
+    check_outcome_fail, check_outcome_success, create_series_raw, create_subaccount, init_nft,
+};
+use near_units::parse_near;
+use nft_contract::common::{env, AccountId, BorshSerialize, U128, U64};
+use nft_contract::{MintConfig, Presale, PresaleProof};
+
+// Leaf of the presale Merkle tree, sha256 of 0x00 and borsh serialized (account_id, allowance)
+fn presale_leaf(account_id: &AccountId, allowance: u64) -> Vec<u8> {
+    let data = (account_id, allowance).try_to_vec().unwrap();
+    env::sha256(&[&[0x00], data.as_slice()].concat())
+}
+
+// Node of the presale Merkle tree, sha256 of 0x01 and the sorted children
+fn hash_pair(a: &[u8], b: &[u8]) -> Vec<u8> {
+    if a <= b {
+        env::sha256(&[&[0x01], a, b].concat())
+    } else {
+        env::sha256(&[&[0x01], b, a].concat())
+    }
+}
+
+// Builds the presale Merkle tree, returns the root and the proof for each leaf.
+// The last node of the odd level is moved to the next level as is
+fn presale_tree(allowances: &[(AccountId, u64)]) -> (Vec<u8>, Vec<Vec<Vec<u8>>>) {
+    let mut level: Vec<Vec<u8>> = allowances
+        .iter()
+        .map(|(account_id, allowance)| presale_leaf(account_id, *allowance))
+        .collect();
+    let mut proofs = vec![vec![]; level.len()];
+    // Index of each leaf's node in the current level
+    let mut positions: Vec<usize> = (0..level.len()).collect();
+    while level.len() > 1 {
+        for (proof, position) in proofs.iter_mut().zip(positions.iter_mut()) {
+            let sibling = *position ^ 1;
+            if sibling < level.len() {
+                proof.push(level[sibling].clone());
+            }
+            *position /= 2;
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => hash_pair(a, b),
+                [a] => a.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    (level.pop().unwrap_or_default(), proofs)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn presale_proof(allowance: u64, proof: &[Vec<u8>]) -> PresaleProof {
+    PresaleProof {
+        allowance,
+        proof: proof.iter().map(|hash| hash.clone().into()).collect(),
+    }
+}
+
+/*
+Test vectors for the presale Merkle tree of
+`alice.near` with allowance 2, `bob.near` with 1 and `carol.near` with 5
+*/
+#[test]
+fn presale_merkle_test_vectors() {
+    let allowances = [
+        ("alice.near".parse().unwrap(), 2),
+        ("bob.near".parse().unwrap(), 1),
+        ("carol.near".parse().unwrap(), 5),
+    ];
+    let leaves: Vec<String> = allowances
+        .iter()
+        .map(|(account_id, allowance)| to_hex(&presale_leaf(account_id, *allowance)))
+        .collect();
+    assert_eq!(
+        leaves,
+        vec![
+            "eceef427f89a70f5e01cdea111b551285be9a57f810b8e5b17b92836e71115ab",
+            "88e2c0ccf2a873aeb43ed8b3b82066463258e36c6ad2036e78b61530dd8b59f8",
+            "194d8cb116e39913bfa69d676d4b2e72d7cf308274d4f5988aedd6637b5234c2",
+        ]
+    );
+    let (root, proofs) = presale_tree(&allowances);
+    assert_eq!(
+        to_hex(&root),
+        "dd7c6d8ea0eef829275745b8998d92a368e2618414537aec77779cc197f1abd6"
+    );
+    // `carol.near` has no pair on the first level, so its leaf is a node of the second one
+    assert_eq!(
+        proofs[0],
+        vec![presale_leaf(&allowances[1].0, 1), presale_leaf(&allowances[2].0, 5)]
+    );
+    for ((account_id, allowance), proof) in allowances.iter().zip(proofs.iter()) {
+        let leaf = presale_leaf(account_id, *allowance);
+        let computed = proof.iter().fold(leaf, |hash, sibling| hash_pair(&hash, sibling));
+        assert_eq!(computed, root);
+    }
+}
+
+/*
+- Panics during the presale if there is no proof
+- Panics if the proof is invalid or belongs to another account
+- Panics if the account exceeds its presale allowance
+- Mints with the presale price during the presale
+- Anyone can mint without a proof after the presale
+*/
+#[tokio::test]
+async fn nft_public_mint_presale() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+    let user3 = create_subaccount(&worker, &owner, "user3").await?;
+    let user4 = create_subaccount(&worker, &owner, "user4").await?;
+    let series =
+        create_series_raw(&worker, nft.id().clone(), &user1, Some(10), HashMap::new()).await?;
+
+    let allowances = [
+        (user2.id().as_ref().parse().unwrap(), 2),
+        (user3.id().as_ref().parse().unwrap(), 1),
+    ];
+    let (root, proofs) = presale_tree(&allowances);
+    let presale = Presale {
+        merkle_root: root.into(),
+        end: U64(u64::MAX),
+        price: Some(U128(parse_near!("0.5 N"))),
+    };
+    let config = MintConfig {
+        price: U128(parse_near!("1 N")),
+        start: None,
+        end: None,
+        limit_per_account: None,
+        allowlist_enabled: false,
+        primary_split: None,
+        presale: Some(presale.clone()),
+    };
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_set_mint_config")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "config": config,
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+
+    // No proof
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Presale requires a proof").await;
+
+    // Wrong allowance
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "presale_proof": presale_proof(3, &proofs[0]),
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Invalid presale proof").await;
+
+    // Proof of another account
+    let outcome = user4
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "presale_proof": presale_proof(2, &proofs[0]),
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Invalid presale proof").await;
+
+    // Presale price is less than the public one
+    for _ in 0..2 {
+        let outcome = user2
+            .call(&worker, nft.id().clone(), "nft_public_mint")
+            .args_json(serde_json::json!({
+                "token_series_id": series,
+                "presale_proof": presale_proof(2, &proofs[0]),
+            }))?
+            .deposit(parse_near!("0.6 N"))
+            .transact()
+            .await?;
+        check_outcome_success(outcome.status).await;
+    }
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "presale_proof": presale_proof(2, &proofs[0]),
+        }))?
+        .deposit(parse_near!("0.6 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Presale allowance exceeded").await;
+    let outcome = user3
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "presale_proof": presale_proof(1, &proofs[1]),
+        }))?
+        .deposit(parse_near!("0.6 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let presale_minted: U64 = nft
+        .view(
+            &worker,
+            "nft_presale_minted",
+            serde_json::json!({ "token_series_id": series, "account_id": user2.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(presale_minted, U64(2));
+
+    // Public phase after the presale
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_set_mint_config")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "config": MintConfig {
+                presale: Some(Presale {
+                    end: U64(1),
+                    ..presale
+                }),
+                ..config
+            },
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let outcome = user4
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(parse_near!("1.1 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    Ok(())
+}
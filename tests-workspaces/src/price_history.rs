@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+
+use crate::utils::{
+    create_series, create_subaccount, deposit, init_market, init_nft, mint_token, nft_approve,
+    offer, price_with_fees,
+};
+use nft_bid_market::{PriceRecord, PriceStats};
+
+/*
+- Completed sale is recorded per token, NFT contract and token type
+- The recorded price doesn't include the buyer fees
+- Stats contain the last price, floor price, volume and number of sales
+*/
+#[tokio::test]
+async fn price_history() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+
+    let series = create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    let sale_conditions = HashMap::from([("near".parse().unwrap(), 10000.into())]);
+    nft_approve(
+        &worker,
+        nft.id().clone(),
+        market.id().clone(),
+        &user1,
+        token1.clone(),
+        sale_conditions.clone(),
+        series.clone(),
+    )
+    .await;
+    let sale_price = sale_conditions[&"near".parse().unwrap()];
+    let price = price_with_fees(&worker, &market, sale_conditions).await?;
+    offer(
+        &worker,
+        nft.id().clone(),
+        market.id().clone(),
+        &user2,
+        token1.clone(),
+        price,
+    )
+    .await;
+
+    let token_history: Vec<PriceRecord> = market
+        .view(
+            &worker,
+            "get_token_price_history",
+            serde_json::json!({
+                "nft_contract_id": nft.id(),
+                "token_id": token1
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(token_history.len(), 1);
+    assert_eq!(token_history[0].price, sale_price);
+    let contract_history: Vec<PriceRecord> = market
+        .view(
+            &worker,
+            "get_contract_price_history",
+            serde_json::json!({ "nft_contract_id": nft.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(contract_history, token_history);
+    let token_type_history: Vec<PriceRecord> = market
+        .view(
+            &worker,
+            "get_token_type_price_history",
+            serde_json::json!({ "token_type": series })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(token_type_history, token_history);
+
+    let stats: PriceStats = market
+        .view(
+            &worker,
+            "get_contract_price_stats",
+            serde_json::json!({
+                "nft_contract_id": nft.id(),
+                "ft_token_id": "near",
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(stats.last_price, Some(sale_price));
+    assert_eq!(stats.floor_price, Some(sale_price));
+    assert_eq!(stats.volume, sale_price);
+    assert_eq!(stats.count, 1);
+    assert_eq!(stats.time_weighted_price, Some(sale_price));
+    Ok(())
+}
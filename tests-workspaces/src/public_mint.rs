@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use crate::utils::{
+    check_outcome_fail, check_outcome_success, create_series_raw, create_subaccount, init_nft,
+};
+use near_units::parse_near;
+use nft_contract::common::{U128, U64};
+use nft_contract::MintConfig;
+
+/*
+- `nft_set_mint_config` can only be called by the owner of the series
+- Panics if the primary split doesn't sum up to 100%
+- Panics if public minting is not enabled
+- Panics if minting hasn't started or has ended
+- Panics if the deposit doesn't cover the price and storage
+- Panics if the account exceeds the limit per account
+- Panics if the account is not in the allowlist or exceeds its allowance
+- Panics if the maximum number of tokens have already been minted
+*/
+#[tokio::test]
+async fn nft_public_mint_negative() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+    let user3 = create_subaccount(&worker, &owner, "user3").await?;
+    let series =
+        create_series_raw(&worker, nft.id().clone(), &user1, Some(3), HashMap::new()).await?;
+    let config = MintConfig {
+        price: U128(parse_near!("1 N")),
+        start: None,
+        end: None,
+        limit_per_account: Some(1),
+        allowlist_enabled: false,
+        primary_split: None,
+        presale: None,
+    };
+
+    // Only owner of the series can set the config
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_set_mint_config")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "config": config,
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "permission denied").await;
+
+    // Primary split should sum up to 100%
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_set_mint_config")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "config": MintConfig {
+                primary_split: Some(HashMap::from([(user1.id().as_ref().parse().unwrap(), 5000)])),
+                ..config.clone()
+            },
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "primary split should sum up to 10000").await;
+
+    // Public minting is not enabled
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(parse_near!("2 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Public minting is not enabled").await;
+
+    // Minting has ended
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_set_mint_config")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "config": MintConfig {
+                end: Some(U64(1)),
+                ..config.clone()
+            },
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(parse_near!("2 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Minting has ended").await;
+
+    // Minting hasn't started yet
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_set_mint_config")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "config": MintConfig {
+                start: Some(U64(u64::MAX)),
+                ..config.clone()
+            },
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(parse_near!("2 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Minting hasn't started yet").await;
+
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_set_mint_config")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "config": config,
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+
+    // Deposit doesn't cover the price
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "to cover the price and storage").await;
+
+    // Limit per account
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(parse_near!("2 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(parse_near!("2 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Mint limit per account exceeded").await;
+
+    // Allowlist
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_set_mint_config")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "config": MintConfig {
+                limit_per_account: None,
+                allowlist_enabled: true,
+                ..config.clone()
+            },
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_set_mint_allowlist")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "allowances": [[user2.id(), 2]],
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let outcome = user3
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(parse_near!("2 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Account is not in the allowlist").await;
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(parse_near!("2 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(parse_near!("2 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Allowance exceeded").await;
+
+    // Max tokens
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_set_mint_allowlist")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "allowances": [[user3.id(), 5]],
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let outcome = user3
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(parse_near!("2 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let outcome = user3
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(parse_near!("2 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Max token minted").await;
+    Ok(())
+}
+
+/*
+- Mints a token to the receiver
+- Remaining supply, allowance and config are returned by the views
+*/
+#[tokio::test]
+async fn nft_public_mint_positive() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+    let user3 = create_subaccount(&worker, &owner, "user3").await?;
+    let series = create_series_raw(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        Some(10),
+        HashMap::from([(owner.id(), 1000)]),
+    )
+    .await?;
+    let config = MintConfig {
+        price: U128(parse_near!("1 N")),
+        start: None,
+        end: None,
+        limit_per_account: Some(3),
+        allowlist_enabled: false,
+        primary_split: None,
+        presale: None,
+    };
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_set_mint_config")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "config": config,
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let mint_config: Option<MintConfig> = nft
+        .view(
+            &worker,
+            "nft_mint_config",
+            serde_json::json!({ "token_series_id": series })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(mint_config, Some(config));
+
+    let token_id: String = user2
+        .call(&worker, nft.id().clone(), "nft_public_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "receiver_id": user3.id(),
+        }))?
+        .deposit(parse_near!("1.1 N"))
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(token_id, format!("{}:1", series));
+    let owner_id: String = nft
+        .view(
+            &worker,
+            "nft_token",
+            serde_json::json!({ "token_id": token_id })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json::<serde_json::Value>()?["owner_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(owner_id, user3.id().to_string());
+    // TODO: check that proceeds are split by royalty after workspaces updated
+
+    let remaining_supply: Option<U64> = nft
+        .view(
+            &worker,
+            "nft_series_remaining_supply",
+            serde_json::json!({ "token_series_id": series })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(remaining_supply, Some(U64(9)));
+    // Allowance is counted for the buyer, not the receiver
+    let allowance: Option<U64> = nft
+        .view(
+            &worker,
+            "nft_mint_allowance",
+            serde_json::json!({ "token_series_id": series, "account_id": user2.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(allowance, Some(U64(2)));
+    Ok(())
+}
@@ -4,8 +4,8 @@ use std::{
 };
 
 use crate::utils::{
-    check_outcome_fail, create_series, create_series_raw, deposit, init_market, init_nft,
-    mint_token, nft_approve, offer,
+    check_outcome_fail, check_outcome_success, create_series, create_series_raw,
+    create_subaccount, deposit, init_market, init_nft, mint_token, nft_approve, offer,
 };
 use near_contract_standards::non_fungible_token::Token;
 use near_units::{parse_gas, parse_near};
@@ -65,6 +65,8 @@ async fn nft_on_approve_negative() -> anyhow::Result<()> {
                 start: None,
                 end: None,
                 origins: None,
+                allowed_buyers: None,
+                settle_at_end: None,
             })).to_string()
         }))?
         .transact()
@@ -89,6 +91,8 @@ async fn nft_on_approve_negative() -> anyhow::Result<()> {
                 start: None,
                 end: None,
                 origins: None,
+                allowed_buyers: None,
+                settle_at_end: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -110,6 +114,8 @@ async fn nft_on_approve_negative() -> anyhow::Result<()> {
                 start: None,
                 end: None,
                 origins: None,
+                allowed_buyers: None,
+                settle_at_end: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -186,6 +192,8 @@ async fn nft_on_approve_positive() -> anyhow::Result<()> {
                 start: None,
                 end: None,
                 origins: None,
+                allowed_buyers: None,
+                settle_at_end: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -221,7 +229,7 @@ async fn nft_on_approve_positive() -> anyhow::Result<()> {
     - Should panic if the deposit equal to 0
     - Should panic if the NFT can't be bought by `ft_token_id`
 - If the `attached_deposit` is equal to the price + fees
-  -  panics if number of payouts plus number of bids exceeds 10
+  -  panics if number of fees plus number of bids exceeds 10, before the token is transferred
 - If the `attached_deposit` is not equal to the price + fees
   - should panic if `ft_token_id` is not supported
   - panics if the bid smaller or equal to the previous one
@@ -282,6 +290,8 @@ async fn offer_negative() -> anyhow::Result<()> {
                 start: Some(U64(epoch_plus_waiting_time as u64)),
                 end: None,
                 origins: None,
+                allowed_buyers: None,
+                settle_at_end: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -451,19 +461,53 @@ async fn offer_negative() -> anyhow::Result<()> {
             "nft_contract_id": nft.id(),
             "token_id": token1,
             "ft_token_id": "near",
+            "origins": too_much_origins,
         }))?
         .deposit(price.into())
         .gas(parse_gas!("300 Tgas") as u64)
         .transact()
         .await?;
-    // Promise of offer returning empty value, because of panic on nft_transfer_payout, but
-    // TODO: we need to check Failure on nft contract when workspaces add feature to check not only FinalExecutionStatus
-    if let near_primitives::views::FinalExecutionStatus::SuccessValue(empty_string) = outcome.status
-    {
-        assert!(empty_string.is_empty())
-    } else {
-        panic!("Expected failure {:?}", outcome.status)
-    };
+    check_outcome_fail(outcome.status, "Too many fees and bid refunds for the payout").await;
+
+    // The token isn't transferred and the sale with its bid stays
+    let token: Token = nft
+        .view(
+            &worker,
+            "nft_token",
+            serde_json::json!({ "token_id": token1 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(token.owner_id.as_str(), user1.id().as_ref());
+    let sale: Option<SaleJson> = market
+        .view(
+            &worker,
+            "get_sale",
+            serde_json::json!({
+                "nft_contract_id": nft.id(),
+                "token_id": token1
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    let sale = sale.expect("The sale was removed");
+    assert_eq!(sale.bids[&"near".parse::<AccountId>().unwrap()].len(), 1);
+    // Only the previous bid is held by the market, the deposit of the purchase is returned
+    let escrowed: HashMap<AccountId, U128> = market
+        .view(
+            &worker,
+            "get_escrowed_by_bidder",
+            serde_json::json!({ "account_id": user2.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(escrowed.get(&"near".parse().unwrap()), Some(&U128(500)));
 
     Ok(())
 }
@@ -761,6 +805,8 @@ async fn accept_offer_negative() -> anyhow::Result<()> {
                 start: None,
                 end: Some(U64(epoch_plus_waiting_time as u64)),
                 origins: None,
+                allowed_buyers: None,
+                settle_at_end: None,
             })).to_string()
         }))?
         .deposit(parse_near!("1 N"))
@@ -1238,3 +1284,798 @@ async fn remove_sale_positive() -> anyhow::Result<()> {
     assert!(sale_json.is_none());
     Ok(())
 }
+
+/*
+- Only buyers from `allowed_buyers` can make an offer
+- Private sale is returned by `get_sales_by_allowed_buyer`
+- Duplicated `allowed_buyers` are stored once and the sale can still be bought
+*/
+#[tokio::test]
+async fn offer_private_sale() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+    let user3 = create_subaccount(&worker, &owner, "user3").await?;
+
+    let series = create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    user1
+        .call(&worker, nft.id().clone(), "nft_approve")
+        .args_json(serde_json::json!({
+            "token_id": token1,
+            "account_id": market.id(),
+            "msg": serde_json::json!(ArgsKind::Sale(SaleArgs {
+                sale_conditions: HashMap::from([("near".parse().unwrap(), 10000.into())]),
+                token_type: Some(series),
+                start: None,
+                end: None,
+                origins: None,
+                allowed_buyers: Some(vec![
+                    user2.id().as_ref().parse().unwrap(),
+                    user2.id().as_ref().parse().unwrap(),
+                ]),
+                settle_at_end: None,
+            })).to_string()
+        }))?
+        .deposit(parse_near!("1 N"))
+        .gas(parse_gas!("200 Tgas") as u64)
+        .transact()
+        .await?;
+
+    let outcome = user3
+        .call(&worker, market.id().clone(), "offer")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+        }))?
+        .deposit(900)
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "The sale is reserved for other buyers").await;
+
+    let sales: Vec<SaleJson> = market
+        .view(
+            &worker,
+            "get_sales_by_allowed_buyer",
+            serde_json::json!({
+                "account_id": user2.id(),
+                "from_index": "0",
+                "limit": 10
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(sales.len(), 1);
+    assert_eq!(sales[0].token_id, token1);
+    assert_eq!(
+        sales[0].allowed_buyers,
+        Some(vec![user2.id().as_ref().parse().unwrap()])
+    );
+
+    let outcome = user2
+        .call(&worker, market.id().clone(), "offer")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+        }))?
+        .deposit(10300)
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let token: Token = nft
+        .view(
+            &worker,
+            "nft_token",
+            serde_json::json!({ "token_id": token1 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(token.owner_id.as_ref(), user2.id().as_ref());
+    let sales: Vec<SaleJson> = market
+        .view(
+            &worker,
+            "get_sales_by_allowed_buyer",
+            serde_json::json!({
+                "account_id": user2.id(),
+                "from_index": "0",
+                "limit": 10
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(sales.is_empty());
+    Ok(())
+}
+
+/*
+- Should panic unless 1 yoctoNEAR is attached
+- Should panic unless it is called by the creator of the sale
+- Should panic if the end is set before the start of an existing bid
+- Should panic if origins are changed while the sale has bids
+- Changes the sale conditions
+- Removes the token type and updates `get_sales_by_nft_token_type`
+*/
+#[tokio::test]
+async fn update_sale() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+
+    let series = create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    let sale_conditions = HashMap::from([("near".parse().unwrap(), 10000.into())]);
+    nft_approve(
+        &worker,
+        nft.id().clone(),
+        market.id().clone(),
+        &user1,
+        token1.clone(),
+        sale_conditions,
+        series.clone(),
+    )
+    .await;
+    // The bid starts in an hour
+    let since_the_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards");
+    let outcome = user2
+        .call(&worker, market.id().clone(), "offer")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+            "start": U64((since_the_epoch + Duration::from_secs(3600)).as_nanos() as u64),
+            "duration": Duration::from_secs(3600).as_nanos().to_string(),
+        }))?
+        .deposit(900)
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+
+    // Should panic unless 1 yoctoNEAR is attached
+    let outcome = user1
+        .call(&worker, market.id().clone(), "update_sale")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "args": {}
+        }))?
+        .transact()
+        .await?;
+    check_outcome_fail(
+        outcome.status,
+        "Requires attached deposit of exactly 1 yoctoNEAR",
+    )
+    .await;
+
+    // Should panic unless it is called by the creator of the sale
+    let outcome = user2
+        .call(&worker, market.id().clone(), "update_sale")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "args": {}
+        }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Must be sale owner").await;
+
+    // Can't set the end before the start of an active bid
+    let outcome = user1
+        .call(&worker, market.id().clone(), "update_sale")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "args": {
+                "end": U64((since_the_epoch + Duration::from_secs(60)).as_nanos() as u64)
+            }
+        }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_fail(
+        outcome.status,
+        "Can't set the end before the time of an active bid",
+    )
+    .await;
+
+    // The bidder has agreed to the current origins
+    let outcome = user1
+        .call(&worker, market.id().clone(), "update_sale")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "args": {
+                "origins": { "user1.test.near": 500 }
+            }
+        }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Can't change origins while the sale has bids").await;
+
+    // Changes the price and removes the token type
+    let sale: SaleJson = user1
+        .call(&worker, market.id().clone(), "update_sale")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "args": {
+                "sale_conditions": { "near": "20000" },
+                "token_type": null,
+            }
+        }))?
+        .deposit(1)
+        .gas(parse_gas!("100 Tgas") as u64)
+        .transact()
+        .await?
+        .json()?;
+    assert_eq!(
+        sale.sale_conditions
+            .get(&AccountId::new_unchecked("near".to_owned())),
+        Some(&U128(20000))
+    );
+    assert!(sale.token_type.is_none());
+    let sales_by_type: Vec<SaleJson> = market
+        .view(
+            &worker,
+            "get_sales_by_nft_token_type",
+            serde_json::json!({
+                "token_type": series,
+                "from_index": "0",
+                "limit": 10
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(sales_by_type.is_empty());
+    Ok(())
+}
+
+/*
+- Can only be called by the creator of the sale
+- Should panic if there is no bid with given `bidder_id` and `price`
+- Accepts the chosen bid, not the last one
+- Other bids are refunded and the sale is removed
+*/
+#[tokio::test]
+async fn accept_bid() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+    let user3 = create_subaccount(&worker, &owner, "user3").await?;
+
+    let series = create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    let sale_conditions = HashMap::from([("near".parse().unwrap(), 10000.into())]);
+    nft_approve(
+        &worker,
+        nft.id().clone(),
+        market.id().clone(),
+        &user1,
+        token1.clone(),
+        sale_conditions,
+        series,
+    )
+    .await;
+    offer(
+        &worker,
+        nft.id().clone(),
+        market.id().clone(),
+        &user2,
+        token1.clone(),
+        900.into(),
+    )
+    .await;
+    offer(
+        &worker,
+        nft.id().clone(),
+        market.id().clone(),
+        &user3,
+        token1.clone(),
+        1000.into(),
+    )
+    .await;
+
+    // Can only be called by the creator of the sale
+    let outcome = user3
+        .call(&worker, market.id().clone(), "accept_bid")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+            "bidder_id": user2.id(),
+            "price": U128(900),
+        }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Only the sale owner can accept a bid").await;
+
+    // No bid with given price
+    let outcome = user1
+        .call(&worker, market.id().clone(), "accept_bid")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+            "bidder_id": user2.id(),
+            "price": U128(901),
+        }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "No such bid").await;
+
+    // Accepts the first bid
+    let outcome = user1
+        .call(&worker, market.id().clone(), "accept_bid")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+            "bidder_id": user2.id(),
+            "price": U128(900),
+        }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let token: Token = nft
+        .view(
+            &worker,
+            "nft_token",
+            serde_json::json!({ "token_id": token1 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(token.owner_id.as_ref(), user2.id().as_ref());
+    let sale_json: Option<SaleJson> = market
+        .view(
+            &worker,
+            "get_sale",
+            serde_json::json!({
+               "nft_contract_id": nft.id(),
+               "token_id": token1
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(sale_json.is_none());
+    Ok(())
+}
+
+/*
+- Panics if the sale is not settled at the end
+- Should panic if called before the sale ends
+- The seller can't move the end of the ended sale to avoid the settlement
+- Can be called by anyone after the end, executes the highest valid bid
+*/
+#[tokio::test]
+async fn finish_sale() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+    let user3 = create_subaccount(&worker, &owner, "user3").await?;
+
+    let series = create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    let since_the_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards");
+    let waiting_time = Duration::from_secs(15);
+    let epoch_plus_waiting_time = (since_the_epoch + waiting_time).as_nanos();
+    user1
+        .call(&worker, nft.id().clone(), "nft_approve")
+        .args_json(serde_json::json!({
+            "token_id": token1,
+            "account_id": market.id(),
+            "msg": serde_json::json!(ArgsKind::Sale(SaleArgs {
+                sale_conditions: HashMap::from([("near".parse().unwrap(), 10000.into())]),
+                token_type: Some(series),
+                start: None,
+                end: Some(U64(epoch_plus_waiting_time as u64)),
+                origins: None,
+                allowed_buyers: None,
+                settle_at_end: Some(true),
+            })).to_string()
+        }))?
+        .deposit(parse_near!("1 N"))
+        .gas(parse_gas!("200 Tgas") as u64)
+        .transact()
+        .await?;
+    offer(
+        &worker,
+        nft.id().clone(),
+        market.id().clone(),
+        &user2,
+        token1.clone(),
+        900.into(),
+    )
+    .await;
+
+    // Should panic if called before the sale ends
+    let outcome = user3
+        .call(&worker, market.id().clone(), "finish_sale")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+        }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_fail(
+        outcome.status,
+        "Sale can be finalized only after the end time",
+    )
+    .await;
+
+    tokio::time::sleep(waiting_time).await;
+    let outcome = user1
+        .call(&worker, market.id().clone(), "update_sale")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "args": {
+                "end": U64((since_the_epoch + waiting_time * 10).as_nanos() as u64)
+            }
+        }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_fail(
+        outcome.status,
+        "Can't change the end of the ended sale until it's settled",
+    )
+    .await;
+
+    // Anyone can settle the sale after the end
+    let outcome = user3
+        .call(&worker, market.id().clone(), "finish_sale")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+        }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let token: Token = nft
+        .view(
+            &worker,
+            "nft_token",
+            serde_json::json!({ "token_id": token1 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(token.owner_id.as_ref(), user2.id().as_ref());
+    Ok(())
+}
+
+/*
+- The winning bid can't be cancelled or removed by the bidder after the end of the sale,
+  even if it has expired
+- `cancel_expired_bids` and `purge_expired_bids` keep the winning bid until the sale is finished
+*/
+#[tokio::test]
+async fn finish_sale_expired_winning_bid() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+    let user3 = create_subaccount(&worker, &owner, "user3").await?;
+
+    let series = create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    let since_the_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("Time went backwards");
+    let waiting_time = Duration::from_secs(15);
+    let epoch_plus_waiting_time = (since_the_epoch + waiting_time).as_nanos();
+    user1
+        .call(&worker, nft.id().clone(), "nft_approve")
+        .args_json(serde_json::json!({
+            "token_id": token1,
+            "account_id": market.id(),
+            "msg": serde_json::json!(ArgsKind::Sale(SaleArgs {
+                sale_conditions: HashMap::from([("near".parse().unwrap(), 10000.into())]),
+                token_type: Some(series),
+                start: None,
+                end: Some(U64(epoch_plus_waiting_time as u64)),
+                origins: None,
+                allowed_buyers: None,
+                settle_at_end: Some(true),
+            })).to_string()
+        }))?
+        .deposit(parse_near!("1 N"))
+        .gas(parse_gas!("200 Tgas") as u64)
+        .transact()
+        .await?;
+    // The bid is valid at the end of the sale and expires shortly after it
+    let outcome = user2
+        .call(&worker, market.id().clone(), "offer")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+            "duration": (waiting_time + Duration::from_secs(3)).as_nanos().to_string(),
+        }))?
+        .deposit(900)
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    tokio::time::sleep(waiting_time + Duration::from_secs(5)).await;
+
+    let outcome = user3
+        .call(&worker, market.id().clone(), "cancel_bid")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+            "owner_id": user2.id(),
+            "price": "900",
+        }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_fail(
+        outcome.status,
+        "The winning bid can't be cancelled until the sale is finished",
+    )
+    .await;
+    let outcome = user2
+        .call(&worker, market.id().clone(), "remove_bid")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+            "price": "900",
+        }))?
+        .deposit(1)
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_fail(
+        outcome.status,
+        "The winning bid can't be cancelled until the sale is finished",
+    )
+    .await;
+    let outcome = user3
+        .call(&worker, market.id().clone(), "cancel_expired_bids")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+        }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let outcome = user3
+        .call(&worker, market.id().clone(), "purge_expired_bids")
+        .args_json(serde_json::json!({ "limit": 10 }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+
+    let outcome = user3
+        .call(&worker, market.id().clone(), "finish_sale")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+        }))?
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let token: Token = nft
+        .view(
+            &worker,
+            "nft_token",
+            serde_json::json!({ "token_id": token1 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(token.owner_id.as_ref(), user2.id().as_ref());
+    Ok(())
+}
+
+/*
+- Panics if the fees together with the refunds of bids wouldn't fit into the payout of the purchase
+- Panics if accepting the bid with its origins wouldn't fit into the payout
+- The sale with the maximum number of bids can still be bought
+*/
+#[tokio::test]
+async fn offer_bids_fit_payout() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+    let user3 = create_subaccount(&worker, &owner, "user3").await?;
+
+    let series = create_series_raw(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        Some(4),
+        HashMap::from([(user1.id(), 500)]),
+    )
+    .await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    // the protocol fee and 4 origins leave room for 4 bid refunds and the seller
+    user1
+        .call(&worker, nft.id().clone(), "nft_approve")
+        .args_json(serde_json::json!({
+            "token_id": token1,
+            "account_id": market.id(),
+            "msg": serde_json::json!(ArgsKind::Sale(SaleArgs {
+                sale_conditions: HashMap::from([("near".parse().unwrap(), 10000.into())]),
+                token_type: Some(series),
+                start: None,
+                end: None,
+                origins: Some(HashMap::from([
+                    ("acc1.near".parse().unwrap(), 100),
+                    ("acc2.near".parse().unwrap(), 100),
+                    ("acc3.near".parse().unwrap(), 100),
+                    ("acc4.near".parse().unwrap(), 100),
+                ])),
+                allowed_buyers: None,
+                settle_at_end: None,
+            })).to_string()
+        }))?
+        .deposit(parse_near!("1 N"))
+        .gas(parse_gas!("200 Tgas") as u64)
+        .transact()
+        .await?;
+
+    for price in [1000, 2000, 3000] {
+        let outcome = user2
+            .call(&worker, market.id().clone(), "offer")
+            .args_json(serde_json::json!({
+                "nft_contract_id": nft.id(),
+                "token_id": token1,
+                "ft_token_id": "near",
+            }))?
+            .deposit(price)
+            .gas(parse_gas!("300 Tgas") as u64)
+            .transact()
+            .await?;
+        check_outcome_success(outcome.status).await;
+    }
+
+    // accepting the bid pays its 2 origins and refunds 3 other bids
+    let outcome = user2
+        .call(&worker, market.id().clone(), "offer")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+            "origins": {
+                "acc5.near": 100,
+                "acc6.near": 100,
+            }
+        }))?
+        .deposit(5000)
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Too many bids and origins for the payout").await;
+
+    let outcome = user2
+        .call(&worker, market.id().clone(), "offer")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+        }))?
+        .deposit(4000)
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+
+    // the purchase would refund 5 bids
+    let outcome = user2
+        .call(&worker, market.id().clone(), "offer")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+        }))?
+        .deposit(5000)
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Too many bids and origins for the payout").await;
+
+    let outcome = user3
+        .call(&worker, market.id().clone(), "offer")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+            "ft_token_id": "near",
+        }))?
+        .deposit(10300)
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let token: Token = nft
+        .view(
+            &worker,
+            "nft_token",
+            serde_json::json!({ "token_id": token1 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(token.owner_id.as_ref(), user3.id().as_ref());
+    let sale: Option<SaleJson> = market
+        .view(
+            &worker,
+            "get_sale",
+            serde_json::json!({
+                "nft_contract_id": nft.id(),
+                "token_id": token1
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(sale.is_none());
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use near_units::{parse_gas, parse_near};
-use nft_bid_market::SaleJson;
+use nft_bid_market::{Page, SaleJson};
 use nft_contract::common::U64;
 
 use crate::utils::{create_series, deposit, init_market, init_nft, mint_token, nft_approve, offer};
@@ -378,3 +378,235 @@ async fn sale_views() -> anyhow::Result<()> {
     assert!(tokens_series2.contains(&sales_nft_token_type[0].token_id));
     Ok(())
 }
+
+#[tokio::test]
+async fn sales_by_price() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = owner
+        .create_subaccount(&worker, "user1")
+        .initial_balance(parse_near!("100 N"))
+        .transact()
+        .await?
+        .unwrap();
+
+    let series = create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    for price in [30000, 10000, 20000] {
+        let token = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+        nft_approve(
+            &worker,
+            nft.id().clone(),
+            market.id().clone(),
+            &user1,
+            token,
+            HashMap::from([("near".parse().unwrap(), price.into())]),
+            series.clone(),
+        )
+        .await;
+    }
+
+    let supply: U64 = market
+        .view(
+            &worker,
+            "get_supply_by_price",
+            serde_json::json!({
+                "nft_contract_id": nft.id(),
+                "ft_token_id": "near",
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(supply.0, 3);
+    let cheapest: Vec<SaleJson> = market
+        .view(
+            &worker,
+            "get_sales_by_price",
+            serde_json::json!({
+                "nft_contract_id": nft.id(),
+                "ft_token_id": "near",
+                "limit": 2,
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    let prices: Vec<u128> = cheapest
+        .iter()
+        .map(|sale| sale.sale_conditions.values().next().unwrap().0)
+        .collect();
+    assert_eq!(prices, vec![10000, 20000]);
+    let most_expensive: Vec<SaleJson> = market
+        .view(
+            &worker,
+            "get_sales_by_price",
+            serde_json::json!({
+                "nft_contract_id": nft.id(),
+                "ft_token_id": "near",
+                "from_index": "1",
+                "descending": true,
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    let prices: Vec<u128> = most_expensive
+        .iter()
+        .map(|sale| sale.sale_conditions.values().next().unwrap().0)
+        .collect();
+    assert_eq!(prices, vec![20000, 10000]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn sales_page() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = owner
+        .create_subaccount(&worker, "user1")
+        .initial_balance(parse_near!("100 N"))
+        .transact()
+        .await?
+        .unwrap();
+
+    let series = create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    let mut tokens = vec![];
+    for _ in 0..3 {
+        let token = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+        nft_approve(
+            &worker,
+            nft.id().clone(),
+            market.id().clone(),
+            &user1,
+            token.clone(),
+            HashMap::from([("near".parse().unwrap(), 10000.into())]),
+            series.clone(),
+        )
+        .await;
+        tokens.push(token);
+    }
+
+    let first_page: Page<SaleJson> = market
+        .view(
+            &worker,
+            "get_sales_by_owner_id_page",
+            serde_json::json!({ "account_id": user1.id(), "limit": 1 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(first_page.items.len(), 1);
+    assert!(first_page.next_cursor.is_some());
+
+    // Removing the first sale doesn't change the next page
+    user1
+        .call(&worker, market.id().clone(), "remove_sale")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": first_page.items[0].token_id,
+        }))?
+        .deposit(1)
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    let second_page: Page<SaleJson> = market
+        .view(
+            &worker,
+            "get_sales_by_owner_id_page",
+            serde_json::json!({
+                "account_id": user1.id(),
+                "cursor": first_page.next_cursor,
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(second_page.items.len(), 2);
+    assert!(second_page.next_cursor.is_none());
+    let all_sales: Page<SaleJson> = market
+        .view(
+            &worker,
+            "get_sales_by_nft_contract_id_page",
+            serde_json::json!({ "nft_contract_id": nft.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    let token_ids: Vec<String> = all_sales.items.into_iter().map(|s| s.token_id).collect();
+    let second_page_ids: Vec<String> = second_page.items.into_iter().map(|s| s.token_id).collect();
+    assert_eq!(token_ids, second_page_ids);
+    let token_type_sales: Page<SaleJson> = market
+        .view(
+            &worker,
+            "get_sales_by_nft_token_type_page",
+            serde_json::json!({ "token_type": series })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    let token_type_ids: Vec<String> =
+        token_type_sales.items.into_iter().map(|s| s.token_id).collect();
+    assert_eq!(token_type_ids, second_page_ids);
+
+    // Sales with the same price are ordered by token id
+    let first_by_price: Page<SaleJson> = market
+        .view(
+            &worker,
+            "get_sales_by_price_page",
+            serde_json::json!({
+                "nft_contract_id": nft.id(),
+                "ft_token_id": "near",
+                "limit": 1,
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(first_by_price.items[0].token_id, second_page_ids[0]);
+    let second_by_price: Page<SaleJson> = market
+        .view(
+            &worker,
+            "get_sales_by_price_page",
+            serde_json::json!({
+                "nft_contract_id": nft.id(),
+                "ft_token_id": "near",
+                "cursor": first_by_price.next_cursor,
+                "limit": 1,
+            })
+            .to_string()
+            .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(second_by_price.items[0].token_id, second_page_ids[1]);
+    assert_eq!(second_by_price.next_cursor, None);
+
+    // An empty page can't be requested
+    let empty_page = market
+        .view(
+            &worker,
+            "get_sales_page",
+            serde_json::json!({ "limit": 0 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await;
+    assert!(empty_page.is_err());
+    Ok(())
+}
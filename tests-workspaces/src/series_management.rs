@@ -0,0 +1,554 @@
+use std::collections::HashMap;
+
+use crate::utils::{
+    check_outcome_fail, check_outcome_success, create_series_raw, create_subaccount, init_nft,
+    mint_token,
+};
+use crate::permissions::set_private_minting;
+use near_units::parse_near;
+use nft_contract::common::{AccountId, Page};
+use nft_contract::{PendingSeriesOwner, TokenSeriesJson};
+
+/*
+- Can only be called by the owner of the series
+- Panics if `copies` is less than the number of minted tokens
+- Panics if the total royalty payout exceeds 50%
+- Panics if a hash is set without its url or isn't 32 bytes
+- Panics if the series is frozen
+*/
+#[tokio::test]
+async fn nft_update_series_negative() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+
+    let series =
+        create_series_raw(&worker, nft.id().clone(), &user1, Some(4), HashMap::new()).await?;
+    mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+
+    // Only owner of the series can update it
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_update_series")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "update": { "description": "new description" },
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "permission denied").await;
+
+    // Can't cap copies below the minted tokens
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_update_series")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "update": { "copies": 1 },
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(
+        outcome.status,
+        "copies can't be less than the number of minted tokens",
+    )
+    .await;
+
+    // Royalty can't exceed 50%
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_update_series")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "update": { "royalty": { user1.id().as_ref(): 500, user2.id().as_ref(): 5000 } },
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "maximum royalty cap exceeded").await;
+
+    // Hash without url
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_update_series")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "update": { "media_hash": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=" },
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Media hash requires media").await;
+
+    // Frozen series can't be updated
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_update_series")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "update": { "freeze": true },
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_update_series")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "update": { "description": "new description" },
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Series is frozen").await;
+    Ok(())
+}
+
+/*
+- Updates only the given fields of the series metadata
+- `media` and `reference` are updated together with their hashes
+- Updates `copies` and royalty
+- Refunds a deposit
+*/
+#[tokio::test]
+async fn nft_update_series_positive() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+
+    let series = create_series_raw(
+        &worker,
+        nft.id().clone(),
+        &user1,
+        Some(4),
+        HashMap::from([(user1.id(), 500)]),
+    )
+    .await?;
+    mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_update_series")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "update": {
+                "description": "new description",
+                "media": "ipfs://new_media",
+                "media_hash": "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=",
+                "copies": 10,
+                "royalty": { user2.id().as_ref(): 1000 },
+            },
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+
+    let series_json: TokenSeriesJson = nft
+        .view(
+            &worker,
+            "nft_get_series",
+            serde_json::json!({ "token_series_id": series })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(series_json.metadata.title, Some("some title".to_string()));
+    assert_eq!(
+        series_json.metadata.description,
+        Some("new description".to_string())
+    );
+    assert_eq!(series_json.metadata.media, Some("ipfs://new_media".to_string()));
+    assert_eq!(
+        series_json.metadata.media_hash.map(|hash| hash.0),
+        Some(vec![0; 32])
+    );
+    assert_eq!(series_json.metadata.copies, Some(10));
+    assert!(series_json.metadata.updated_at.is_some());
+    assert_eq!(
+        series_json.royalty,
+        HashMap::from([(user2.id().as_ref().parse().unwrap(), 1000)])
+    );
+    assert!(!series_json.frozen);
+    Ok(())
+}
+
+/*
+- Should panic unless 1 yoctoNEAR is attached
+- Can only be called by the owner of the series
+- Panics if the new owner already owns the series
+- Only the pending owner can accept the ownership
+- Panics if there is no pending transfer
+*/
+#[tokio::test]
+async fn nft_series_transfer_ownership_negative() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+    let user3 = create_subaccount(&worker, &owner, "user3").await?;
+    let series =
+        create_series_raw(&worker, nft.id().clone(), &user1, Some(4), HashMap::new()).await?;
+
+    // Should panic unless 1 yoctoNEAR is attached
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_series_transfer_ownership")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "new_owner_id": user2.id(),
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(
+        outcome.status,
+        "Requires attached deposit of exactly 1 yoctoNEAR",
+    )
+    .await;
+
+    // Only owner of the series can transfer it
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_series_transfer_ownership")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "new_owner_id": user2.id(),
+        }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "permission denied").await;
+
+    // Can't transfer to the current owner
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_series_transfer_ownership")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "new_owner_id": user1.id(),
+        }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "The account already owns the series").await;
+
+    // Nothing to accept
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_series_accept_ownership")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "No pending ownership transfer").await;
+
+    // Only the pending owner can accept
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_series_transfer_ownership")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "new_owner_id": user2.id(),
+        }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_series_accept_ownership")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .transact()
+        .await?;
+    check_outcome_fail(
+        outcome.status,
+        "Requires attached deposit of exactly 1 yoctoNEAR",
+    )
+    .await;
+    let outcome = user3
+        .call(&worker, nft.id().clone(), "nft_series_accept_ownership")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "permission denied").await;
+
+    // Cancelled transfer can't be accepted
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_series_transfer_ownership")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "new_owner_id": null,
+        }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_series_accept_ownership")
+        .args_json(serde_json::json!({ "token_series_id": series }))?
+        .deposit(1)
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "No pending ownership transfer").await;
+    Ok(())
+}
+
+/*
+- The new owner becomes the owner of the series after accepting
+- The royalty of the old owner is moved only if `move_royalty` is set
+- Minters of the old owner are removed unless `keep_minters` is set
+- The old owner can't mint anymore
+*/
+#[tokio::test]
+async fn nft_series_transfer_ownership_positive() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+    let user3 = create_subaccount(&worker, &owner, "user3").await?;
+    let royalty = HashMap::from([(user1.id(), 500), (owner.id(), 1000)]);
+    let series1 =
+        create_series_raw(&worker, nft.id().clone(), &user1, Some(4), royalty.clone()).await?;
+    let series2 = create_series_raw(&worker, nft.id().clone(), &user1, Some(4), royalty).await?;
+
+    for (series, new_owner, move_royalty) in [(&series1, &user2, true), (&series2, &user3, false)] {
+        let outcome = user1
+            .call(&worker, nft.id().clone(), "nft_series_grant_minter")
+            .args_json(serde_json::json!({
+                "token_series_id": series,
+                "account_id": owner.id(),
+            }))?
+            .deposit(parse_near!("0.01 N"))
+            .transact()
+            .await?;
+        check_outcome_success(outcome.status).await;
+        let outcome = user1
+            .call(&worker, nft.id().clone(), "nft_series_transfer_ownership")
+            .args_json(serde_json::json!({
+                "token_series_id": series,
+                "new_owner_id": new_owner.id(),
+                "move_royalty": move_royalty,
+                "keep_minters": move_royalty,
+            }))?
+            .deposit(1)
+            .transact()
+            .await?;
+        check_outcome_success(outcome.status).await;
+        let pending_owner: Option<PendingSeriesOwner> = nft
+            .view(
+                &worker,
+                "nft_series_pending_owner",
+                serde_json::json!({ "token_series_id": series })
+                    .to_string()
+                    .into_bytes(),
+            )
+            .await?
+            .json()?;
+        assert_eq!(
+            pending_owner,
+            Some(PendingSeriesOwner {
+                owner_id: new_owner.id().as_ref().parse().unwrap(),
+                move_royalty,
+                keep_minters: move_royalty,
+            })
+        );
+        let outcome = new_owner
+            .call(&worker, nft.id().clone(), "nft_series_accept_ownership")
+            .args_json(serde_json::json!({ "token_series_id": series }))?
+            .deposit(1)
+            .transact()
+            .await?;
+        check_outcome_success(outcome.status).await;
+    }
+
+    let series1_json: TokenSeriesJson = nft
+        .view(
+            &worker,
+            "nft_get_series",
+            serde_json::json!({ "token_series_id": series1 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(series1_json.owner_id.as_str(), user2.id().as_ref());
+    assert_eq!(
+        series1_json.royalty,
+        HashMap::from([
+            (user2.id().as_ref().parse().unwrap(), 500),
+            (owner.id().as_ref().parse().unwrap(), 1000)
+        ])
+    );
+    let series2_json: TokenSeriesJson = nft
+        .view(
+            &worker,
+            "nft_get_series",
+            serde_json::json!({ "token_series_id": series2 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(series2_json.owner_id.as_str(), user3.id().as_ref());
+    assert_eq!(
+        series2_json.royalty,
+        HashMap::from([
+            (user1.id().as_ref().parse().unwrap(), 500),
+            (owner.id().as_ref().parse().unwrap(), 1000)
+        ])
+    );
+    for (series, minters) in [
+        (&series1, vec![owner.id().as_ref().parse::<AccountId>().unwrap()]),
+        (&series2, vec![]),
+    ] {
+        let series_minters: Vec<AccountId> = nft
+            .view(
+                &worker,
+                "nft_series_minters",
+                serde_json::json!({ "token_series_id": series })
+                    .to_string()
+                    .into_bytes(),
+            )
+            .await?
+            .json()?;
+        assert_eq!(series_minters, minters);
+    }
+    let outcome = owner
+        .call(&worker, nft.id().clone(), "nft_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series2,
+            "receiver_id": owner.id()
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "permission denied").await;
+
+    // Only the new owner can mint
+    let outcome = user1
+        .call(&worker, nft.id().clone(), "nft_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series1,
+            "receiver_id": user1.id()
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "permission denied").await;
+    mint_token(&worker, nft.id().clone(), &user2, user2.id(), &series1).await?;
+    Ok(())
+}
+
+/*
+- Only the owner of the series can grant and deny minters
+- Granted minter can mint the series, denied can't
+- Minters of the series are returned by the views
+- Contract level private minting is checked for the minters too
+*/
+#[tokio::test]
+async fn nft_series_minters() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+    let user2 = create_subaccount(&worker, &owner, "user2").await?;
+    let series =
+        create_series_raw(&worker, nft.id().clone(), &user1, Some(4), HashMap::new()).await?;
+
+    // Only owner of the series can grant minters
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_series_grant_minter")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "account_id": user2.id(),
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "permission denied").await;
+
+    let is_added: bool = user1
+        .call(&worker, nft.id().clone(), "nft_series_grant_minter")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "account_id": user2.id(),
+        }))?
+        .deposit(parse_near!("0.01 N"))
+        .transact()
+        .await?
+        .json()?;
+    assert!(is_added);
+    let minters: Vec<AccountId> = nft
+        .view(
+            &worker,
+            "nft_series_minters",
+            serde_json::json!({ "token_series_id": series })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(minters, vec![user2.id().as_ref().parse::<AccountId>().unwrap()]);
+    let minters_page: Page<AccountId> = nft
+        .view(
+            &worker,
+            "nft_series_minters_page",
+            serde_json::json!({ "token_series_id": series, "limit": 1 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(minters_page.items, minters);
+    assert_eq!(minters_page.next_cursor, None);
+    let is_minter: bool = nft
+        .view(
+            &worker,
+            "nft_series_is_minter",
+            serde_json::json!({ "token_series_id": series, "account_id": user2.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert!(is_minter);
+
+    mint_token(&worker, nft.id().clone(), &user2, user2.id(), &series).await?;
+
+    // Private minting is checked for the series minters too
+    set_private_minting(&worker, nft.id().clone(), &owner, true).await;
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "receiver_id": user2.id()
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "Access to mint is denied for this contract").await;
+    set_private_minting(&worker, nft.id().clone(), &owner, false).await;
+
+    let is_removed: bool = user1
+        .call(&worker, nft.id().clone(), "nft_series_deny_minter")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "account_id": user2.id(),
+        }))?
+        .transact()
+        .await?
+        .json()?;
+    assert!(is_removed);
+    let outcome = user2
+        .call(&worker, nft.id().clone(), "nft_mint")
+        .args_json(serde_json::json!({
+            "token_series_id": series,
+            "receiver_id": user2.id()
+        }))?
+        .deposit(parse_near!("1 N"))
+        .transact()
+        .await?;
+    check_outcome_fail(outcome.status, "permission denied").await;
+    Ok(())
+}
@@ -3,7 +3,7 @@ use crate::utils::{
     create_series, mint_token
 };
 use nft_contract::TokenSeriesJson;
-use nft_contract::common::{AccountId, U128};
+use nft_contract::common::{AccountId, Page, U128};
 
 /*
 - Panics if the series wasn't found
@@ -214,3 +214,48 @@ async fn series_views_nft_supply_for_series() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/*
+- Returns up to `limit` series after the `cursor`
+- `next_cursor` is `None` on the last page
+*/
+#[tokio::test]
+async fn series_views_nft_series_page() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+
+    for _ in 0..3 {
+        create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    }
+
+    let page: Page<TokenSeriesJson> = nft
+        .view(
+            &worker,
+            "nft_series_page",
+            serde_json::json!({ "limit": 2 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(page.items.len(), 2);
+    assert_eq!(page.next_cursor, Some("2".to_string()));
+
+    let page: Page<TokenSeriesJson> = nft
+        .view(
+            &worker,
+            "nft_series_page",
+            serde_json::json!({ "cursor": page.next_cursor, "limit": 2 })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.next_cursor, None);
+
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use crate::utils::{
+    check_outcome_success, create_series, create_subaccount, deposit, init_market, init_nft,
+    mint_token, nft_approve,
+};
+use near_units::parse_gas;
+use nft_bid_market::CollectionStats;
+use nft_contract::common::{AccountId, U128};
+
+/*
+- Counts listings of the collection and its token type
+- Floor price is the lowest price of the listings
+- Floor price is recalculated when the floor sale is removed, also for the token type
+*/
+#[tokio::test]
+async fn collection_stats() -> anyhow::Result<()> {
+    let worker = workspaces::sandbox();
+    let owner = worker.root_account();
+    let nft = init_nft(&worker, owner.id()).await?;
+    let market = init_market(&worker, worker.root_account().id(), vec![nft.id()]).await?;
+
+    let user1 = create_subaccount(&worker, &owner, "user1").await?;
+
+    let series = create_series(&worker, nft.id().clone(), &user1, owner.id().clone()).await?;
+    let token1 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    let token2 = mint_token(&worker, nft.id().clone(), &user1, user1.id(), &series).await?;
+    deposit(&worker, market.id().clone(), &user1).await;
+    for (token, price) in [(token1.clone(), 10000), (token2.clone(), 20000)] {
+        nft_approve(
+            &worker,
+            nft.id().clone(),
+            market.id().clone(),
+            &user1,
+            token,
+            HashMap::from([("near".parse().unwrap(), price.into())]),
+            series.clone(),
+        )
+        .await;
+    }
+    let near: AccountId = "near".parse().unwrap();
+
+    let stats: CollectionStats = market
+        .view(
+            &worker,
+            "get_collection_stats",
+            serde_json::json!({ "nft_contract_id": nft.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(stats.listings, 2);
+    assert_eq!(stats.auctions, 0);
+    assert_eq!(stats.floor_prices.get(&near), Some(&U128(10000)));
+    let token_type_stats: CollectionStats = market
+        .view(
+            &worker,
+            "get_token_type_stats",
+            serde_json::json!({ "token_type": series })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(token_type_stats, stats);
+
+    let outcome = user1
+        .call(&worker, market.id().clone(), "remove_sale")
+        .args_json(serde_json::json!({
+            "nft_contract_id": nft.id(),
+            "token_id": token1,
+        }))?
+        .deposit(1)
+        .gas(parse_gas!("300 Tgas") as u64)
+        .transact()
+        .await?;
+    check_outcome_success(outcome.status).await;
+    let stats: CollectionStats = market
+        .view(
+            &worker,
+            "get_collection_stats",
+            serde_json::json!({ "nft_contract_id": nft.id() })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(stats.listings, 1);
+    assert_eq!(stats.floor_prices.get(&near), Some(&U128(20000)));
+    let token_type_stats: CollectionStats = market
+        .view(
+            &worker,
+            "get_token_type_stats",
+            serde_json::json!({ "token_type": series })
+                .to_string()
+                .into_bytes(),
+        )
+        .await?
+        .json()?;
+    assert_eq!(token_type_stats, stats);
+    Ok(())
+}
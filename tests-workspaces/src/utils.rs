@@ -1,7 +1,6 @@
 use near_contract_standards::non_fungible_token::Token;
 use near_units::parse_gas;
 use near_units::parse_near;
-use nft_bid_market::Fees;
 use nft_bid_market::{ArgsKind, SaleArgs};
 use nft_contract::common::TokenMetadata;
 use nft_contract::common::{AccountId, U128, U64};
@@ -173,6 +172,8 @@ pub async fn nft_approve(
                 start: None,
                 end: None,
                 origins: None,
+                allowed_buyers: None,
+                settle_at_end: None,
             })).to_string()
         }))
         .unwrap()
@@ -289,7 +290,7 @@ pub async fn nft_transfer_payout_helper(
     user2: &Account,
     user3: &Account,
     royalty: HashMap<&workspaces::AccountId, u64>,
-    fees: Fees,
+    memo: Option<String>,
     balance: U128,
 ) -> Payout {
     let series = create_series_raw(worker, nft.id().clone(), user1, Some(4), royalty)
@@ -335,7 +336,7 @@ pub async fn nft_transfer_payout_helper(
             "receiver_id": user3.id(),
             "token_id": token_id,
             "approval_id": approval_id,
-            "memo": serde_json::json!(fees).to_string(),
+            "memo": memo,
             "balance": balance,
             "max_len_payout": 10,
         }))